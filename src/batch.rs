@@ -0,0 +1,115 @@
+use crate::shared::{Shared, SharedPointerKind};
+use crate::{Change, Interval, IntervalTree, Node};
+
+fn changed_interval<I>(change: &Change<I>) -> &I {
+    match change {
+        Change::Added(interval) | Change::Removed(interval) => interval,
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> Node<T, P> {
+    /// Apply changes to distinct intervals, in sorted order, to a tree,
+    /// reusing any subtree that none of the changes fall into
+    fn apply_changes(
+        tree: &Option<Shared<Node<T, P>, P>>,
+        changes: &[Change<Interval<T, P>>],
+    ) -> Option<Shared<Node<T, P>, P>> {
+        if changes.is_empty() {
+            return tree.clone();
+        }
+        let node = match tree {
+            Some(node) => node,
+            None => {
+                let added = changes
+                    .iter()
+                    .filter_map(|change| match change {
+                        Change::Added(interval) => Some(interval.clone()),
+                        Change::Removed(_) => None,
+                    })
+                    .collect::<Vec<_>>();
+                return Self::from_sorted(&added);
+            }
+        };
+        let (less, rest) =
+            changes.split_at(changes.partition_point(|c| *changed_interval(c) < node.interval));
+        let (here, greater) = match rest.split_first() {
+            Some((change, greater)) if *changed_interval(change) == node.interval => {
+                (Some(change), greater)
+            }
+            _ => (None, rest),
+        };
+        let left = Self::apply_changes(&node.left, less);
+        let right = Self::apply_changes(&node.right, greater);
+        if let Some(Change::Removed(_)) = here {
+            Self::join2(left, right)
+        } else if Self::same_subtree(&node.left, &left) && Self::same_subtree(&node.right, &right) {
+            tree.clone()
+        } else {
+            Some(Shared::new(Self::join(left, node.interval.clone(), right)))
+        }
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> IntervalTree<T, P> {
+    /// Construct a new IntervalTree with a batch of changes applied
+    ///
+    /// The changes are sorted and applied in a single pass down the tree, so
+    /// changes to neighbouring intervals share the copying of the path to
+    /// them, and subtrees no change falls into are shared with this tree.
+    /// Adding an interval that is already present or empty, or removing one
+    /// that is not present, does nothing. When several changes name the same interval, the
+    /// last one wins.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{Change, IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = (0..10)
+    ///     .map(|i| Interval::new(Included(i), Excluded(i + 1)))
+    ///     .collect();
+    ///
+    /// let tree = tree.apply_batch(vec![
+    ///     Change::Removed(Interval::new(Included(3), Excluded(4))),
+    ///     Change::Added(Interval::new(Included(20), Unbounded)),
+    ///     Change::Removed(Interval::new(Included(5), Excluded(6))),
+    ///     Change::Added(Interval::new(Included(3), Included(5))),
+    /// ]);
+    ///
+    /// let mut intervals = tree.query_point(&4).collect::<Vec<Interval<u8>>>();
+    /// intervals.sort();
+    /// assert_eq!(
+    ///     intervals,
+    ///     vec![
+    ///         Interval::new(Included(3), Included(5)),
+    ///         Interval::new(Included(4), Excluded(5)),
+    ///     ]
+    /// );
+    /// assert_eq!(tree.iter().count(), 10);
+    /// ```
+    pub fn apply_batch<I>(&self, changes: I) -> IntervalTree<T, P>
+    where
+        I: IntoIterator<Item = Change<Interval<T, P>>>,
+    {
+        let mut changes = changes
+            .into_iter()
+            .filter(|change| match change {
+                Change::Added(interval) => Interval::valid(interval),
+                Change::Removed(_) => true,
+            })
+            .collect::<Vec<_>>();
+        // A stable sort keeps changes to the same interval in the order given
+        changes.sort_by(|a, b| changed_interval(a).cmp(changed_interval(b)));
+        changes.dedup_by(|later, earlier| {
+            let same = changed_interval(later) == changed_interval(earlier);
+            if same {
+                std::mem::swap(later, earlier);
+            }
+            same
+        });
+        let tree = IntervalTree {
+            root: Node::apply_changes(&self.root, &changes),
+        };
+        tree.debug_check_invariants();
+        tree
+    }
+}
@@ -0,0 +1,683 @@
+use std::iter::FromIterator;
+use std::ops::Bound;
+use std::ops::Bound::*;
+
+use crate::interval::*;
+use crate::shared::{Shared, SharedPointerKind};
+use crate::{ArcK, DefaultSharedPointerKind, InvariantViolation};
+
+/// The most entries a node may hold before it is split
+const MAX_ENTRIES: usize = 32;
+
+/// The fewest entries a node other than the root may hold
+const MIN_ENTRIES: usize = MAX_ENTRIES / 2;
+
+/// A reference to a child node along with a summary of its intervals, so
+/// that children can be pruned without following the pointer
+struct Child<T: Ord + Clone, P: SharedPointerKind> {
+    /// The least interval in the child, which also has the least lower bound
+    first: Interval<T, P>,
+    /// The greatest upper bound in the child
+    max: Bound<T>,
+    node: Shared<BNode<T, P>, P>,
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> Clone for Child<T, P> {
+    fn clone(&self) -> Self {
+        Child {
+            first: self.first.clone(),
+            max: self.max.clone(),
+            node: self.node.clone(),
+        }
+    }
+}
+
+enum BNode<T: Ord + Clone, P: SharedPointerKind> {
+    /// Sorted intervals
+    Leaf(Vec<Interval<T, P>>),
+    /// Sorted children, all of the same height
+    Internal(Vec<Child<T, P>>),
+}
+
+/// Split items into as few runs as possible of at most `MAX_ENTRIES` each,
+/// with lengths that differ by at most one
+fn chunk_evenly<E>(items: Vec<E>) -> Vec<Vec<E>> {
+    let chunks = items.len().div_ceil(MAX_ENTRIES);
+    let mut result = Vec::with_capacity(chunks);
+    let mut items = items.into_iter();
+    for i in 0..chunks {
+        let remaining = items.len();
+        let size = remaining.div_ceil(chunks - i);
+        result.push(items.by_ref().take(size).collect());
+    }
+    result
+}
+
+/// The index of the child that would contain the given interval
+fn child_index<T: Ord + Clone, P: SharedPointerKind>(
+    children: &[Child<T, P>],
+    interval: &Interval<T, P>,
+) -> usize {
+    children
+        .partition_point(|child| child.first <= *interval)
+        .saturating_sub(1)
+}
+
+fn max_high<'a, T: Ord + Clone + 'a>(highs: impl Iterator<Item = &'a Bound<T>>) -> Bound<T> {
+    highs
+        .max_by(|a, b| high_bound_cmp(a, b))
+        .cloned()
+        .expect("nodes are never empty when summarized")
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> BNode<T, P> {
+    fn len(&self) -> usize {
+        match self {
+            BNode::Leaf(intervals) => intervals.len(),
+            BNode::Internal(children) => children.len(),
+        }
+    }
+
+    fn first(&self) -> &Interval<T, P> {
+        match self {
+            BNode::Leaf(intervals) => &intervals[0],
+            BNode::Internal(children) => &children[0].first,
+        }
+    }
+
+    fn last(&self) -> &Interval<T, P> {
+        match self {
+            BNode::Leaf(intervals) => &intervals[intervals.len() - 1],
+            BNode::Internal(children) => children[children.len() - 1].node.last(),
+        }
+    }
+
+    fn max(&self) -> Bound<T> {
+        match self {
+            BNode::Leaf(intervals) => max_high(intervals.iter().map(|i| i.high())),
+            BNode::Internal(children) => max_high(children.iter().map(|c| &c.max)),
+        }
+    }
+
+    fn into_child(self) -> Child<T, P> {
+        Child {
+            first: self.first().clone(),
+            max: self.max(),
+            node: Shared::new(self),
+        }
+    }
+
+    /// Split the node in two if it holds too many entries
+    fn split_if_full(self) -> Vec<Self> {
+        if self.len() <= MAX_ENTRIES {
+            return vec![self];
+        }
+        match self {
+            BNode::Leaf(mut intervals) => {
+                let right = intervals.split_off(intervals.len() / 2);
+                vec![BNode::Leaf(intervals), BNode::Leaf(right)]
+            }
+            BNode::Internal(mut children) => {
+                let right = children.split_off(children.len() / 2);
+                vec![BNode::Internal(children), BNode::Internal(right)]
+            }
+        }
+    }
+
+    /// Combine two adjacent nodes of the same height, splitting the result
+    /// again if it holds too many entries
+    fn concat(left: &Self, right: &Self) -> Vec<Self> {
+        let combined = match (left, right) {
+            (BNode::Leaf(left), BNode::Leaf(right)) => {
+                BNode::Leaf(left.iter().chain(right).cloned().collect())
+            }
+            (BNode::Internal(left), BNode::Internal(right)) => {
+                BNode::Internal(left.iter().chain(right).cloned().collect())
+            }
+            _ => unreachable!("siblings have the same height"),
+        };
+        combined.split_if_full()
+    }
+
+    /// Return the node with the interval added, split in two if it
+    /// overflowed, or `None` if the interval is already present
+    fn insert(&self, interval: Interval<T, P>) -> Option<Vec<Self>> {
+        let node = match self {
+            BNode::Leaf(intervals) => {
+                let index = intervals.binary_search(&interval).err()?;
+                let mut intervals = intervals.clone();
+                intervals.insert(index, interval);
+                BNode::Leaf(intervals)
+            }
+            BNode::Internal(children) => {
+                let index = child_index(children, &interval);
+                let replaced = children[index].node.insert(interval)?;
+                let mut new_children = children[..index].to_vec();
+                new_children.extend(replaced.into_iter().map(BNode::into_child));
+                new_children.extend_from_slice(&children[index + 1..]);
+                BNode::Internal(new_children)
+            }
+        };
+        Some(node.split_if_full())
+    }
+
+    /// Return the node with the interval removed along with the removed
+    /// interval, or `None` if it is not present
+    ///
+    /// The returned node may hold fewer than `MIN_ENTRIES` entries.
+    fn remove(&self, interval: &Interval<T, P>) -> Option<(Self, Interval<T, P>)> {
+        match self {
+            BNode::Leaf(intervals) => {
+                let index = intervals.binary_search(interval).ok()?;
+                let mut intervals = intervals.clone();
+                let removed = intervals.remove(index);
+                Some((BNode::Leaf(intervals), removed))
+            }
+            BNode::Internal(children) => {
+                let index = child_index(children, interval);
+                let (new_child, removed) = children[index].node.remove(interval)?;
+                let mut new_children = children.clone();
+                if new_child.len() >= MIN_ENTRIES {
+                    new_children[index] = new_child.into_child();
+                } else {
+                    // Refill the child from a sibling, merging the two if they fit in one node
+                    let (start, merged) = if index + 1 < children.len() {
+                        (index, Self::concat(&new_child, &children[index + 1].node))
+                    } else {
+                        (
+                            index - 1,
+                            Self::concat(&children[index - 1].node, &new_child),
+                        )
+                    };
+                    new_children
+                        .splice(start..start + 2, merged.into_iter().map(BNode::into_child));
+                }
+                Some((BNode::Internal(new_children), removed))
+            }
+        }
+    }
+
+    /// Return the height of the node if it and its descendants are
+    /// correctly ordered, sized and summarized
+    fn check_invariants(&self, is_root: bool) -> Result<usize, InvariantViolation> {
+        let min_entries = match (is_root, self) {
+            (false, _) => MIN_ENTRIES,
+            (true, BNode::Leaf(_)) => 1,
+            (true, BNode::Internal(_)) => 2,
+        };
+        if self.len() < min_entries || self.len() > MAX_ENTRIES {
+            return Err(InvariantViolation::EntryCount { found: self.len() });
+        }
+        match self {
+            BNode::Leaf(intervals) => {
+                if !intervals.iter().all(Interval::valid) {
+                    Err(InvariantViolation::EmptyInterval)
+                } else if !intervals.windows(2).all(|pair| pair[0] < pair[1]) {
+                    Err(InvariantViolation::Unordered)
+                } else {
+                    Ok(1)
+                }
+            }
+            BNode::Internal(children) => {
+                let mut height = None;
+                for (i, child) in children.iter().enumerate() {
+                    let child_height = child.node.check_invariants(false)?;
+                    if *height.get_or_insert(child_height) != child_height {
+                        return Err(InvariantViolation::UnevenLeaves);
+                    }
+                    if child.first != *child.node.first() {
+                        return Err(InvariantViolation::MinMismatch);
+                    }
+                    if child.max != child.node.max() {
+                        return Err(InvariantViolation::MaxMismatch);
+                    }
+                    if i > 0 && *children[i - 1].node.last() >= child.first {
+                        return Err(InvariantViolation::Unordered);
+                    }
+                }
+                Ok(height.map_or(1, |height| height + 1))
+            }
+        }
+    }
+}
+
+/// An Iterator over the intervals of an IntervalBTree that overlap a query,
+/// in sorted order
+struct Query<'a, T: Ord + Clone, P: SharedPointerKind> {
+    stack: Vec<&'a BNode<T, P>>,
+    leaf: std::slice::Iter<'a, Interval<T, P>>,
+    query: Interval<T, P>,
+}
+
+impl<'a, T: Ord + Clone, P: SharedPointerKind> Iterator for Query<'a, T, P> {
+    type Item = &'a Interval<T, P>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            while let Some(interval) = self.leaf.next() {
+                if !low_reaches(interval.low(), self.query.high()) {
+                    self.leaf = [].iter();
+                    break;
+                }
+                if self.query.overlaps(interval) {
+                    return Some(interval);
+                }
+            }
+            match self.stack.pop()? {
+                BNode::Leaf(intervals) => self.leaf = intervals.iter(),
+                BNode::Internal(children) => {
+                    for child in children.iter().rev() {
+                        if high_reaches(&child.max, self.query.low())
+                            && low_reaches(child.first.low(), self.query.high())
+                        {
+                            self.stack.push(&child.node);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A persistent interval tree built from wide B+-tree nodes
+///
+/// This stores up to 32 intervals per leaf and 32 children per internal node,
+/// along with the least interval and greatest upper bound of each child. That
+/// means far fewer allocations and pointers to follow than `IntervalTree`,
+/// which makes it a better fit for very large collections. Queries return
+/// intervals in sorted order.
+///
+/// It supports the core of the `IntervalTree` API: insertion and removal,
+/// the owned and borrowing queries, `contains`, `first`, `last` and
+/// `check_invariants`. Set operations, cursors, nearest neighbour searches
+/// and the other bulk operations are only available on `IntervalTree`.
+///
+/// # Example
+/// ```
+/// # use std::ops::Bound::*;
+/// # use im_interval_tree::{IntervalBTree, Interval};
+/// let tree : IntervalBTree<u32> = (0..1000)
+///     .map(|i| Interval::new(Included(i), Excluded(i + 10)))
+///     .collect();
+/// let tree = tree.remove(&Interval::new(Included(100), Excluded(110)));
+///
+/// let query = tree.query_interval(&Interval::new(Included(105), Excluded(110)));
+/// assert_eq!(query.count(), 13);
+/// ```
+pub struct IntervalBTree<T: Ord + Clone, P: SharedPointerKind = DefaultSharedPointerKind> {
+    root: Option<Shared<BNode<T, P>, P>>,
+}
+
+impl<T: Ord + Clone> IntervalBTree<T> {
+    /// Construct an empty IntervalBTree
+    pub fn new() -> IntervalBTree<T> {
+        Self::new_with_ptr_kind()
+    }
+}
+
+impl<T: Ord + Clone> IntervalBTree<T, ArcK> {
+    /// Construct an empty IntervalBTree that uses `Arc` pointers
+    pub fn new_sync() -> IntervalBTree<T, ArcK> {
+        Self::new_with_ptr_kind()
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> IntervalBTree<T, P> {
+    /// Construct an empty IntervalBTree using any kind of shared pointer
+    pub fn new_with_ptr_kind() -> IntervalBTree<T, P> {
+        IntervalBTree { root: None }
+    }
+
+    /// Construct a new IntervalBTree with the given Interval added
+    ///
+    /// If the interval is empty, the tree is returned unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalBTree, Interval};
+    /// let tree : IntervalBTree<u8> = IntervalBTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Included(2)));
+    /// assert_eq!(
+    ///     tree.iter().collect::<Vec<Interval<u8>>>(),
+    ///     vec![Interval::new(Included(1), Included(2))]
+    /// );
+    /// ```
+    pub fn insert(&self, interval: Interval<T, P>) -> IntervalBTree<T, P> {
+        if !Interval::valid(&interval) {
+            return self.clone();
+        }
+        let new_root = match &self.root {
+            None => BNode::Leaf(vec![interval]),
+            Some(node) => match node.insert(interval) {
+                None => return self.clone(),
+                Some(mut nodes) if nodes.len() == 1 => nodes.pop().unwrap(),
+                Some(nodes) => BNode::Internal(nodes.into_iter().map(BNode::into_child).collect()),
+            },
+        };
+        let tree = IntervalBTree {
+            root: Some(Shared::new(new_root)),
+        };
+        tree.debug_check_invariants();
+        tree
+    }
+
+    /// Construct a new IntervalBTree minus the given Interval, if present
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalBTree, Interval};
+    /// let tree : IntervalBTree<u8> = IntervalBTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Included(2)));
+    /// let tree = tree.insert(Interval::new(Included(1), Included(3)));
+    ///
+    /// let tree = tree.remove(&Interval::new(Included(1), Included(2)));
+    /// assert_eq!(
+    ///     tree.iter().collect::<Vec<Interval<u8>>>(),
+    ///     vec![Interval::new(Included(1), Included(3))]
+    /// );
+    /// ```
+    pub fn remove(&self, interval: &Interval<T, P>) -> IntervalBTree<T, P> {
+        let new_root = match self.root.as_ref().and_then(|node| node.remove(interval)) {
+            None => return self.clone(),
+            Some((new_root, _)) => new_root,
+        };
+        let root = match new_root {
+            BNode::Leaf(intervals) if intervals.is_empty() => None,
+            BNode::Internal(mut children) if children.len() == 1 => {
+                Some(children.pop().unwrap().node)
+            }
+            new_root => Some(Shared::new(new_root)),
+        };
+        let tree = IntervalBTree { root };
+        tree.debug_check_invariants();
+        tree
+    }
+
+    /// Return an Iterator over all the intervals in the tree that overlap
+    /// with the given interval, in sorted order
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalBTree, Interval};
+    /// let tree : IntervalBTree<u8> = IntervalBTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    ///
+    /// let query = tree.query_interval(&Interval::new(Included(3), Included(6)));
+    /// assert_eq!(
+    ///     query.collect::<Vec<Interval<u8>>>(),
+    ///     vec![Interval::new(Included(5), Unbounded)]
+    /// );
+    /// ```
+    pub fn query_interval(
+        &self,
+        interval: &Interval<T, P>,
+    ) -> impl Iterator<Item = Interval<T, P>> + '_ {
+        self.query_interval_ref(interval).cloned()
+    }
+
+    /// Return an Iterator over all the intervals in the tree that contain
+    /// the given point, in sorted order
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalBTree, Interval};
+    /// let tree : IntervalBTree<u8> = IntervalBTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    ///
+    /// let query = tree.query_point(&2);
+    /// assert_eq!(
+    ///     query.collect::<Vec<Interval<u8>>>(),
+    ///     vec![Interval::new(Included(1), Excluded(3))]
+    /// );
+    /// ```
+    pub fn query_point(&self, point: &T) -> impl Iterator<Item = Interval<T, P>> + '_ {
+        self.query_point_ref(point).cloned()
+    }
+
+    /// Return an Iterator over all the intervals in the tree, in sorted order
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalBTree, Interval};
+    /// let tree : IntervalBTree<u8> = IntervalBTree::new();
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    ///
+    /// assert_eq!(
+    ///     tree.iter().collect::<Vec<Interval<u8>>>(),
+    ///     vec![
+    ///         Interval::new(Included(1), Excluded(3)),
+    ///         Interval::new(Included(5), Unbounded),
+    ///     ]
+    /// );
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = Interval<T, P>> + '_ {
+        self.iter_ref().cloned()
+    }
+
+    /// Return an Iterator over references to all the intervals in the tree
+    /// that overlap with the given interval, in sorted order
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalBTree, Interval};
+    /// let tree : IntervalBTree<u8> = IntervalBTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    ///
+    /// let query = tree.query_interval_ref(&Interval::new(Included(3), Included(6)));
+    /// assert_eq!(
+    ///     query.collect::<Vec<&Interval<u8>>>(),
+    ///     vec![&Interval::new(Included(5), Unbounded)]
+    /// );
+    /// ```
+    pub fn query_interval_ref(
+        &self,
+        interval: &Interval<T, P>,
+    ) -> impl Iterator<Item = &Interval<T, P>> + '_ {
+        Query {
+            stack: self.root.iter().map(|node| &**node).collect(),
+            leaf: [].iter(),
+            query: interval.clone(),
+        }
+    }
+
+    /// Return an Iterator over references to all the intervals in the tree
+    /// that contain the given point, in sorted order
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalBTree, Interval};
+    /// let tree : IntervalBTree<u8> = IntervalBTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    ///
+    /// let query = tree.query_point_ref(&2);
+    /// assert_eq!(
+    ///     query.collect::<Vec<&Interval<u8>>>(),
+    ///     vec![&Interval::new(Included(1), Excluded(3))]
+    /// );
+    /// ```
+    pub fn query_point_ref(&self, point: &T) -> impl Iterator<Item = &Interval<T, P>> + '_ {
+        let interval =
+            Interval::new_with_ptr_kind(Included(point.clone()), Included(point.clone()));
+        self.query_interval_ref(&interval)
+    }
+
+    /// Return an Iterator over references to all the intervals in the tree,
+    /// in sorted order
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalBTree, Interval};
+    /// let tree : IntervalBTree<u8> = IntervalBTree::new();
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    ///
+    /// assert_eq!(
+    ///     tree.iter_ref().collect::<Vec<&Interval<u8>>>(),
+    ///     vec![
+    ///         &Interval::new(Included(1), Excluded(3)),
+    ///         &Interval::new(Included(5), Unbounded),
+    ///     ]
+    /// );
+    /// ```
+    pub fn iter_ref(&self) -> impl Iterator<Item = &Interval<T, P>> + '_ {
+        self.query_interval_ref(&Interval::new_with_ptr_kind(Unbounded, Unbounded))
+    }
+
+    /// Check whether the tree contains the given Interval
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalBTree, Interval};
+    /// let tree : IntervalBTree<u8> = IntervalBTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    ///
+    /// assert!(tree.contains(&Interval::new(Included(1), Excluded(3))));
+    /// assert!(!tree.contains(&Interval::new(Included(1), Included(3))));
+    /// ```
+    pub fn contains(&self, interval: &Interval<T, P>) -> bool {
+        let mut current = match &self.root {
+            None => return false,
+            Some(node) => node,
+        };
+        loop {
+            match &**current {
+                BNode::Leaf(intervals) => return intervals.binary_search(interval).is_ok(),
+                BNode::Internal(children) => {
+                    current = &children[child_index(children, interval)].node;
+                }
+            }
+        }
+    }
+
+    /// Return the least interval in the tree
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalBTree, Interval};
+    /// let tree : IntervalBTree<u8> = IntervalBTree::new();
+    /// let tree = tree.insert(Interval::new(Included(2), Excluded(4)));
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(5)));
+    ///
+    /// assert_eq!(tree.first(), Some(Interval::new(Included(1), Excluded(5))));
+    /// ```
+    pub fn first(&self) -> Option<Interval<T, P>> {
+        self.root.as_ref().map(|node| node.first().clone())
+    }
+
+    /// Return the greatest interval in the tree
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalBTree, Interval};
+    /// let tree : IntervalBTree<u8> = IntervalBTree::new();
+    /// let tree = tree.insert(Interval::new(Included(2), Excluded(4)));
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(5)));
+    ///
+    /// assert_eq!(tree.last(), Some(Interval::new(Included(2), Excluded(4))));
+    /// ```
+    pub fn last(&self) -> Option<Interval<T, P>> {
+        self.root.as_ref().map(|node| node.last().clone())
+    }
+
+    /// Check whether two trees share the same root
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.root, &other.root) {
+            (None, None) => true,
+            (Some(a), Some(b)) => Shared::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    /// Verify the structural invariants of the tree
+    ///
+    /// This checks that every interval is non-empty and the intervals are in
+    /// sorted order, that every node other than the root is at least half
+    /// full and no node is overfull, that all leaves are at the same depth,
+    /// and that the cached least interval and greatest upper bound of every
+    /// child match its contents. A tree built only through the public API
+    /// from non-empty intervals should always pass.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalBTree, Interval};
+    /// let tree : IntervalBTree<u32> = (0..1000)
+    ///     .map(|i| Interval::new(Included(i), Excluded(i + 10)))
+    ///     .collect();
+    ///
+    /// assert_eq!(tree.check_invariants(), Ok(()));
+    /// ```
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        match &self.root {
+            None => Ok(()),
+            Some(node) => node.check_invariants(true).map(|_| ()),
+        }
+    }
+
+    #[inline]
+    fn debug_check_invariants(&self) {
+        if cfg!(feature = "debug-invariants") {
+            if let Err(violation) = self.check_invariants() {
+                panic!("IntervalBTree invariant violated: {}", violation);
+            }
+        }
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> Clone for IntervalBTree<T, P> {
+    fn clone(&self) -> Self {
+        IntervalBTree {
+            root: self.root.clone(),
+        }
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> Default for IntervalBTree<T, P> {
+    fn default() -> Self {
+        Self::new_with_ptr_kind()
+    }
+}
+
+/// Builds an IntervalBTree by sorting the intervals and packing them into
+/// full nodes, which is faster than inserting them one by one. Empty
+/// intervals are skipped.
+impl<T: Ord + Clone, P: SharedPointerKind> FromIterator<Interval<T, P>> for IntervalBTree<T, P> {
+    fn from_iter<I: IntoIterator<Item = Interval<T, P>>>(iter: I) -> Self {
+        let mut intervals = iter.into_iter().filter(Interval::valid).collect::<Vec<_>>();
+        intervals.sort_unstable();
+        intervals.dedup();
+
+        let mut level = chunk_evenly(intervals)
+            .into_iter()
+            .map(BNode::Leaf)
+            .collect::<Vec<_>>();
+        while level.len() > 1 {
+            let children = level.into_iter().map(BNode::into_child).collect();
+            level = chunk_evenly(children)
+                .into_iter()
+                .map(BNode::Internal)
+                .collect();
+        }
+        let tree = IntervalBTree {
+            root: level.pop().map(Shared::new),
+        };
+        tree.debug_check_invariants();
+        tree
+    }
+}
@@ -0,0 +1,244 @@
+use std::cmp::Ordering;
+use std::ops::Bound;
+
+use crate::interval::*;
+use crate::shared::{DefaultSharedPointerKind, Shared, SharedPointerKind};
+use crate::{IntervalTree, Node};
+
+/// A Cursor over the intervals of an IntervalTree in sorted order
+///
+/// A cursor either points at an interval in the tree or at a "ghost" position
+/// past the end of the tree. Moving forward from the ghost position wraps
+/// around to the first interval, and moving backward wraps to the last.
+///
+/// Since intervals are their own keys, the current interval can be saved and
+/// later passed to `IntervalTree::cursor_at` to resume from the same place,
+/// even in a newer version of the tree.
+///
+/// # Example
+/// ```
+/// # use std::ops::Bound::*;
+/// # use im_interval_tree::{IntervalTree, Interval};
+/// let tree : IntervalTree<u8> = (0..10)
+///     .map(|i| Interval::new(Included(i), Excluded(i + 1)))
+///     .collect();
+///
+/// let mut cursor = tree.cursor_at(&Interval::new(Included(3), Excluded(4)));
+/// let page = std::iter::from_fn(|| {
+///     let current = cursor.current().cloned();
+///     cursor.move_next();
+///     current
+/// })
+/// .take(3)
+/// .collect::<Vec<_>>();
+/// assert_eq!(page.len(), 3);
+///
+/// let saved = cursor.current().unwrap().clone();
+/// let mut cursor = tree.cursor_at(&saved);
+/// assert_eq!(cursor.current(), Some(&Interval::new(Included(6), Excluded(7))));
+/// assert_eq!(cursor.move_prev(), Some(&Interval::new(Included(5), Excluded(6))));
+/// ```
+pub struct Cursor<'a, T: Ord + Clone, P: SharedPointerKind = DefaultSharedPointerKind> {
+    root: Option<&'a Node<T, P>>,
+    /// The nodes from the root down to the current node, or empty at the ghost position
+    path: Vec<&'a Node<T, P>>,
+}
+
+impl<'a, T: Ord + Clone, P: SharedPointerKind> Cursor<'a, T, P> {
+    /// Return the interval the cursor points at, or `None` at the ghost position
+    pub fn current(&self) -> Option<&'a Interval<T, P>> {
+        self.path.last().map(|node| &node.interval)
+    }
+
+    /// Move to the next interval in sorted order, returning it
+    pub fn move_next(&mut self) -> Option<&'a Interval<T, P>> {
+        match self.path.last() {
+            None => self.descend_first(self.root),
+            Some(node) => match &node.right {
+                Some(right_tree) => self.descend_first(Some(right_tree)),
+                None => self.ascend_from(|parent| &parent.left),
+            },
+        }
+        self.current()
+    }
+
+    /// Move to the previous interval in sorted order, returning it
+    pub fn move_prev(&mut self) -> Option<&'a Interval<T, P>> {
+        match self.path.last() {
+            None => self.descend_last(self.root),
+            Some(node) => match &node.left {
+                Some(left_tree) => self.descend_last(Some(left_tree)),
+                None => self.ascend_from(|parent| &parent.right),
+            },
+        }
+        self.current()
+    }
+
+    /// Move to the least interval that is greater than or equal to the given
+    /// interval, returning it
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    ///
+    /// let mut cursor = tree.cursor_front();
+    /// assert_eq!(
+    ///     cursor.seek(&Interval::new(Included(2), Included(2))),
+    ///     Some(&Interval::new(Included(5), Unbounded))
+    /// );
+    /// assert_eq!(cursor.seek(&Interval::new(Included(6), Included(6))), None);
+    /// ```
+    pub fn seek(&mut self, interval: &Interval<T, P>) -> Option<&'a Interval<T, P>> {
+        self.seek_by(|node| node.interval.cmp(interval) != Ordering::Less)
+    }
+
+    /// Move to the least interval whose lower bound is greater than or equal
+    /// to the given bound, returning it
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Excluded(1), Excluded(2)));
+    ///
+    /// let mut cursor = tree.cursor_front();
+    /// assert_eq!(
+    ///     cursor.seek_low(&Excluded(1)),
+    ///     Some(&Interval::new(Excluded(1), Excluded(2)))
+    /// );
+    /// ```
+    pub fn seek_low(&mut self, low: &Bound<T>) -> Option<&'a Interval<T, P>> {
+        self.seek_by(|node| low_bound_cmp(node.interval.low(), low) != Ordering::Less)
+    }
+
+    /// Move to the first node for which `is_at_or_after` holds, which must be
+    /// false for some prefix of the tree and true for the rest
+    fn seek_by<F>(&mut self, is_at_or_after: F) -> Option<&'a Interval<T, P>>
+    where
+        F: Fn(&Node<T, P>) -> bool,
+    {
+        self.path.clear();
+        let mut found = 0;
+        let mut current = self.root;
+        while let Some(node) = current {
+            self.path.push(node);
+            if is_at_or_after(node) {
+                found = self.path.len();
+                current = node.left.as_deref();
+            } else {
+                current = node.right.as_deref();
+            }
+        }
+        self.path.truncate(found);
+        self.current()
+    }
+
+    fn descend_first(&mut self, mut current: Option<&'a Node<T, P>>) {
+        while let Some(node) = current {
+            self.path.push(node);
+            current = node.left.as_deref();
+        }
+    }
+
+    fn descend_last(&mut self, mut current: Option<&'a Node<T, P>>) {
+        while let Some(node) = current {
+            self.path.push(node);
+            current = node.right.as_deref();
+        }
+    }
+
+    /// Climb until reaching a parent that we arrived at from the given side
+    fn ascend_from<F>(&mut self, side: F)
+    where
+        F: Fn(&'a Node<T, P>) -> &'a Option<Shared<Node<T, P>, P>>,
+    {
+        while let Some(child) = self.path.pop() {
+            match self.path.last() {
+                Some(parent) => {
+                    if let Some(from) = side(parent) {
+                        if std::ptr::eq(&**from, child) {
+                            return;
+                        }
+                    }
+                }
+                None => return,
+            }
+        }
+    }
+}
+
+impl<'a, T: Ord + Clone, P: SharedPointerKind> Clone for Cursor<'a, T, P> {
+    fn clone(&self) -> Self {
+        Cursor {
+            root: self.root,
+            path: self.path.clone(),
+        }
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> IntervalTree<T, P> {
+    /// Return a Cursor pointing at the least interval in the tree
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    ///
+    /// let mut cursor = tree.cursor_front();
+    /// assert_eq!(cursor.current(), Some(&Interval::new(Included(1), Excluded(3))));
+    /// assert_eq!(cursor.move_next(), Some(&Interval::new(Included(5), Unbounded)));
+    /// assert_eq!(cursor.move_next(), None);
+    /// ```
+    pub fn cursor_front(&self) -> Cursor<'_, T, P> {
+        let mut cursor = self.cursor_ghost();
+        cursor.move_next();
+        cursor
+    }
+
+    /// Return a Cursor pointing at the greatest interval in the tree
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    ///
+    /// let mut cursor = tree.cursor_back();
+    /// assert_eq!(cursor.current(), Some(&Interval::new(Included(5), Unbounded)));
+    /// assert_eq!(cursor.move_prev(), Some(&Interval::new(Included(1), Excluded(3))));
+    /// assert_eq!(cursor.move_prev(), None);
+    /// ```
+    pub fn cursor_back(&self) -> Cursor<'_, T, P> {
+        let mut cursor = self.cursor_ghost();
+        cursor.move_prev();
+        cursor
+    }
+
+    /// Return a Cursor pointing at the least interval that is greater than or
+    /// equal to the given interval
+    ///
+    /// This is equivalent to `tree.cursor_front().seek(interval)`
+    pub fn cursor_at(&self, interval: &Interval<T, P>) -> Cursor<'_, T, P> {
+        let mut cursor = self.cursor_ghost();
+        cursor.seek(interval);
+        cursor
+    }
+
+    fn cursor_ghost(&self) -> Cursor<'_, T, P> {
+        Cursor {
+            root: self.root.as_deref(),
+            path: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::cmp::Ordering;
+
+use crate::shared::SharedPointerKind;
+use crate::{Interval, IntervalTree, Node};
+
+/// A difference between two versions of an IntervalTree
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Change<I> {
+    /// An interval present in the new version but not the old one
+    Added(I),
+    /// An interval present in the old version but not the new one
+    Removed(I),
+}
+
+enum DiffItem<'a, T: Ord + Clone, P: SharedPointerKind> {
+    /// Every interval in the subtree rooted at this node, not yet expanded
+    Subtree(&'a Node<T, P>),
+    /// Only the interval stored at a node
+    Single(&'a Interval<T, P>),
+}
+
+/// The intervals of one tree still to be compared, with the least on top
+struct Frontier<'a, T: Ord + Clone, P: SharedPointerKind> {
+    stack: Vec<DiffItem<'a, T, P>>,
+}
+
+impl<'a, T: Ord + Clone, P: SharedPointerKind> Frontier<'a, T, P> {
+    fn new(tree: &'a IntervalTree<T, P>) -> Self {
+        Frontier {
+            stack: tree
+                .root
+                .iter()
+                .map(|node| DiffItem::Subtree(&**node))
+                .collect(),
+        }
+    }
+
+    /// Replace the subtree on top with its left subtree, interval and right subtree
+    fn expand(&mut self) {
+        if let Some(DiffItem::Subtree(node)) = self.stack.pop() {
+            if let Some(right_tree) = &node.right {
+                self.stack.push(DiffItem::Subtree(right_tree));
+            }
+            self.stack.push(DiffItem::Single(&node.interval));
+            if let Some(left_tree) = &node.left {
+                self.stack.push(DiffItem::Subtree(left_tree));
+            }
+        }
+    }
+
+    /// Pop the least interval, expanding subtrees as needed
+    fn pop_interval(&mut self) -> Option<&'a Interval<T, P>> {
+        loop {
+            match self.stack.last()? {
+                DiffItem::Subtree(_) => self.expand(),
+                DiffItem::Single(interval) => {
+                    let interval = *interval;
+                    self.stack.pop();
+                    return Some(interval);
+                }
+            }
+        }
+    }
+}
+
+/// An Iterator over the changes between two trees, in sorted order
+struct Diff<'a, T: Ord + Clone, P: SharedPointerKind> {
+    old: Frontier<'a, T, P>,
+    new: Frontier<'a, T, P>,
+}
+
+impl<'a, T: Ord + Clone, P: SharedPointerKind> Iterator for Diff<'a, T, P> {
+    type Item = Change<Interval<T, P>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match (self.old.stack.last(), self.new.stack.last()) {
+                (None, None) => return None,
+                (Some(_), None) => {
+                    return self.old.pop_interval().cloned().map(Change::Removed);
+                }
+                (None, Some(_)) => {
+                    return self.new.pop_interval().cloned().map(Change::Added);
+                }
+                (Some(DiffItem::Subtree(old)), Some(DiffItem::Subtree(new))) => {
+                    if std::ptr::eq(*old, *new) {
+                        self.old.stack.pop();
+                        self.new.stack.pop();
+                    } else if old.height >= new.height {
+                        self.old.expand();
+                    } else {
+                        self.new.expand();
+                    }
+                }
+                (Some(DiffItem::Subtree(_)), Some(DiffItem::Single(_))) => self.old.expand(),
+                (Some(DiffItem::Single(_)), Some(DiffItem::Subtree(_))) => self.new.expand(),
+                (Some(DiffItem::Single(old)), Some(DiffItem::Single(new))) => match old.cmp(new) {
+                    Ordering::Less => {
+                        let removed = (*old).clone();
+                        self.old.stack.pop();
+                        return Some(Change::Removed(removed));
+                    }
+                    Ordering::Greater => {
+                        let added = (*new).clone();
+                        self.new.stack.pop();
+                        return Some(Change::Added(added));
+                    }
+                    Ordering::Equal => {
+                        self.old.stack.pop();
+                        self.new.stack.pop();
+                    }
+                },
+            }
+        }
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> IntervalTree<T, P> {
+    /// Return an Iterator over the changes that turn this tree into `new`,
+    /// in sorted order
+    ///
+    /// Subtrees that the two trees share are skipped without being visited,
+    /// so diffing two versions derived from one another costs time
+    /// proportional to the number of changes times the height of the trees,
+    /// rather than to their size.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{Change, IntervalTree, Interval};
+    /// let old : IntervalTree<u8> = (0..100)
+    ///     .map(|i| Interval::new(Included(i), Excluded(i + 1)))
+    ///     .collect();
+    /// let new = old
+    ///     .remove(&Interval::new(Included(10), Excluded(11)))
+    ///     .insert(Interval::new(Included(10), Unbounded));
+    ///
+    /// assert_eq!(
+    ///     old.diff(&new).collect::<Vec<_>>(),
+    ///     vec![
+    ///         Change::Removed(Interval::new(Included(10), Excluded(11))),
+    ///         Change::Added(Interval::new(Included(10), Unbounded)),
+    ///     ]
+    /// );
+    /// ```
+    pub fn diff<'a>(&'a self, new: &'a Self) -> impl Iterator<Item = Change<Interval<T, P>>> + 'a {
+        Diff {
+            old: Frontier::new(self),
+            new: Frontier::new(new),
+        }
+    }
+}
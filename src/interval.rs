@@ -3,12 +3,15 @@ use quickcheck::*;
 
 use std::cmp::Ord;
 use std::cmp::Ordering;
-use std::fmt::Debug;
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Debug, Display};
 use std::hash::{Hash, Hasher};
 use std::ops::Bound;
 use std::ops::Bound::*;
+use std::str::FromStr;
 
-use crate::shared::Shared;
+use crate::shared::*;
 
 pub fn low_bound_cmp<T: Ord>(a: &Bound<T>, b: &Bound<T>) -> Ordering {
     match (a, b) {
@@ -34,16 +37,6 @@ pub fn low_bound_cmp<T: Ord>(a: &Bound<T>, b: &Bound<T>) -> Ordering {
     }
 }
 
-pub fn low_bound_min<T: Ord + Clone>(
-    a: &Shared<Bound<T>>,
-    b: &Shared<Bound<T>>,
-) -> Shared<Bound<T>> {
-    match low_bound_cmp(a, b) {
-        Ordering::Less => a.clone(),
-        _ => b.clone(),
-    }
-}
-
 pub fn high_bound_cmp<T: Ord + Clone>(a: &Bound<T>, b: &Bound<T>) -> Ordering {
     match (a, b) {
         (Included(high1), Included(high2)) => high1.cmp(high2),
@@ -68,21 +61,36 @@ pub fn high_bound_cmp<T: Ord + Clone>(a: &Bound<T>, b: &Bound<T>) -> Ordering {
     }
 }
 
-pub fn high_bound_max<T: Ord + Clone>(
-    a: &Shared<Bound<T>>,
-    b: &Shared<Bound<T>>,
-) -> Shared<Bound<T>> {
-    match high_bound_cmp(a, b) {
-        Ordering::Less => b.clone(),
-        _ => a.clone(),
+/// Whether an interval with the given upper bound ends at or after the given lower bound
+pub fn high_reaches<T: Ord>(high: &Bound<T>, low: &Bound<T>) -> bool {
+    match (high, low) {
+        (Included(high), Included(low)) => high >= low,
+        (Included(high), Excluded(low))
+        | (Excluded(high), Included(low))
+        | (Excluded(high), Excluded(low)) => high > low,
+        _ => true,
+    }
+}
+
+/// Whether an interval with the given lower bound starts at or before the given upper bound
+pub fn low_reaches<T: Ord>(low: &Bound<T>, high: &Bound<T>) -> bool {
+    match (low, high) {
+        (Included(low), Included(high)) => low <= high,
+        (Included(low), Excluded(high))
+        | (Excluded(low), Included(high))
+        | (Excluded(low), Excluded(high)) => low < high,
+        _ => true,
     }
 }
 
 /// A data structure for representing intervals
-#[derive(Debug, Clone)]
-pub struct Interval<T: Ord + Clone> {
-    pub(crate) low: Shared<Bound<T>>,
-    pub(crate) high: Shared<Bound<T>>,
+///
+/// The bounds are stored as the pointer kind `P` decides, which defaults to
+/// sharing them behind `Rc` (or `Arc` with the `arc` feature). The inline
+/// kinds store the bounds in the interval itself.
+pub struct Interval<T: Ord + Clone, P: SharedPointerKind = DefaultSharedPointerKind> {
+    pub(crate) low: Stored<Bound<T>, P>,
+    pub(crate) high: Stored<Bound<T>, P>,
 }
 
 impl<T: Ord + Clone> Interval<T> {
@@ -95,13 +103,42 @@ impl<T: Ord + Clone> Interval<T> {
     /// let interval = Interval::new(Included(3), Excluded(5));
     /// ```
     pub fn new(low: Bound<T>, high: Bound<T>) -> Interval<T> {
+        Self::new_with_ptr_kind(low, high)
+    }
+}
+
+impl<T: Ord + Clone> Interval<T, ArcK> {
+    /// Construct a new Interval from two Bounds, using `Arc` pointers
+    ///
+    /// # Example
+    /// ```
+    /// # use im_interval_tree::IntervalSync;
+    /// use std::ops::Bound::*;
+    /// let interval = IntervalSync::new_sync(Included(3), Excluded(5));
+    /// ```
+    pub fn new_sync(low: Bound<T>, high: Bound<T>) -> Interval<T, ArcK> {
+        Self::new_with_ptr_kind(low, high)
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> Interval<T, P> {
+    /// Construct a new Interval from two Bounds, using any kind of shared pointer
+    ///
+    /// # Example
+    /// ```
+    /// # use im_interval_tree::{Interval, RcK};
+    /// use std::ops::Bound::*;
+    /// let interval : Interval<u8, RcK> = Interval::new_with_ptr_kind(Included(3), Excluded(5));
+    /// ```
+    pub fn new_with_ptr_kind(low: Bound<T>, high: Bound<T>) -> Interval<T, P> {
         Interval {
-            low: Shared::new(low),
-            high: Shared::new(high),
+            low: Stored::new(low),
+            high: Stored::new(high),
         }
     }
 
-    fn valid(interval: &Interval<T>) -> bool {
+    /// Whether the interval contains any value, meaning low is not above high
+    pub(crate) fn valid(interval: &Interval<T, P>) -> bool {
         match (&*interval.low, &*interval.high) {
             (Included(low), Included(high)) => low <= high,
 
@@ -183,6 +220,43 @@ impl<T: Ord + Clone> Interval<T> {
         left_side_lte && right_side_gte
     }
 
+    /// Construct a new Interval by applying a function to the value of each bound
+    ///
+    /// Whether each bound is included, excluded or unbounded is unchanged.
+    /// This suits increasing functions; for a decreasing function the
+    /// bounds would come out inverted, so use `map_reversed` instead.
+    ///
+    /// # Example
+    /// ```
+    /// # use im_interval_tree::Interval;
+    /// # use std::ops::Bound::*;
+    /// let interval = Interval::new(Included(3), Unbounded);
+    /// assert_eq!(interval.map(|x| x * 10), Interval::new(Included(30), Unbounded));
+    /// ```
+    pub fn map<U: Ord + Clone, F: Fn(&T) -> U>(&self, f: F) -> Interval<U, P> {
+        Interval::new_with_ptr_kind(self.low().as_ref().map(&f), self.high().as_ref().map(&f))
+    }
+
+    /// Construct a new Interval by applying a decreasing function to the
+    /// value of each bound, swapping the bounds so the result is not inverted
+    ///
+    /// The mapped upper bound becomes the lower bound and vice versa, each
+    /// keeping whether it is included, excluded or unbounded.
+    ///
+    /// # Example
+    /// ```
+    /// # use im_interval_tree::Interval;
+    /// # use std::ops::Bound::*;
+    /// let interval = Interval::new(Included(1), Excluded(3));
+    /// assert_eq!(
+    ///     interval.map_reversed(|x| -x),
+    ///     Interval::new(Excluded(-3), Included(-1))
+    /// );
+    /// ```
+    pub fn map_reversed<U: Ord + Clone, F: Fn(&T) -> U>(&self, f: F) -> Interval<U, P> {
+        Interval::new_with_ptr_kind(self.high().as_ref().map(&f), self.low().as_ref().map(&f))
+    }
+
     /// Return the lower bound
     ///
     /// # Example
@@ -210,21 +284,39 @@ impl<T: Ord + Clone> Interval<T> {
     }
 }
 
-impl<T: Ord + Clone> PartialEq for Interval<T> {
+impl<T: Ord + Clone, P: SharedPointerKind> Clone for Interval<T, P> {
+    fn clone(&self) -> Self {
+        Interval {
+            low: self.low.clone(),
+            high: self.high.clone(),
+        }
+    }
+}
+
+impl<T: Ord + Clone + Debug, P: SharedPointerKind> Debug for Interval<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Interval")
+            .field("low", &self.low)
+            .field("high", &self.high)
+            .finish()
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> PartialEq for Interval<T, P> {
     fn eq(&self, other: &Self) -> bool {
         self.low == other.low && self.high == other.high
     }
 }
 
-impl<T: Ord + Clone> Eq for Interval<T> {}
+impl<T: Ord + Clone, P: SharedPointerKind> Eq for Interval<T, P> {}
 
-impl<T: Ord + Clone> PartialOrd for Interval<T> {
+impl<T: Ord + Clone, P: SharedPointerKind> PartialOrd for Interval<T, P> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-impl<T: Ord + Clone> Ord for Interval<T> {
+impl<T: Ord + Clone, P: SharedPointerKind> Ord for Interval<T, P> {
     fn cmp(&self, other: &Self) -> Ordering {
         let low_bound_cmp = low_bound_cmp(&*self.low, &*other.low);
         if low_bound_cmp == Ordering::Equal {
@@ -235,25 +327,181 @@ impl<T: Ord + Clone> Ord for Interval<T> {
     }
 }
 
-impl<T: Ord + Clone + Hash> Hash for Interval<T> {
+impl<T: Ord + Clone + Hash, P: SharedPointerKind> Hash for Interval<T, P> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.low.hash(state);
         self.high.hash(state);
     }
 }
 
+/// Formats an Interval in mathematical notation
+///
+/// # Example
+/// ```
+/// # use im_interval_tree::Interval;
+/// # use std::ops::Bound::*;
+/// assert_eq!(Interval::new(Included(1), Excluded(3)).to_string(), "[1, 3)");
+/// assert_eq!(Interval::new(Unbounded, Included(5)).to_string(), "(-inf, 5]");
+/// assert_eq!(Interval::new(Excluded(5), Unbounded).to_string(), "(5, +inf)");
+/// assert_eq!(format!("{:>8}", Interval::new(Included(1), Excluded(3))), "  [1, 3)");
+/// ```
+impl<T: Ord + Clone + Display, P: SharedPointerKind> Display for Interval<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let low = match self.low() {
+            Included(low) => format!("[{}", low),
+            Excluded(low) => format!("({}", low),
+            Unbounded => "(-inf".to_string(),
+        };
+        let high = match self.high() {
+            Included(high) => format!("{}]", high),
+            Excluded(high) => format!("{})", high),
+            Unbounded => "+inf)".to_string(),
+        };
+        // Format the whole interval first so width and alignment apply to it
+        f.pad(&format!("{}, {}", low, high))
+    }
+}
+
+/// An error returned when parsing an Interval from a string fails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseIntervalError<E> {
+    /// The string does not start with `[` or `(`
+    InvalidLowBracket(Option<char>),
+    /// The string does not end with `]` or `)`
+    InvalidHighBracket(Option<char>),
+    /// There is no `,` separating the two bounds
+    MissingSeparator,
+    /// There is more than one `,`, so it is unclear where the bounds are split
+    AmbiguousSeparator,
+    /// An infinite bound was given a closed bracket
+    ClosedInfinity,
+    /// The lower bound could not be parsed
+    InvalidLow(E),
+    /// The upper bound could not be parsed
+    InvalidHigh(E),
+    /// The lower bound is above the upper bound
+    Empty,
+}
+
+impl<E: Display> Display for ParseIntervalError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseIntervalError::InvalidLowBracket(Some(c)) => {
+                write!(f, "expected '[' or '(' but found '{}'", c)
+            }
+            ParseIntervalError::InvalidLowBracket(None) => {
+                write!(f, "expected '[' or '(' but found end of input")
+            }
+            ParseIntervalError::InvalidHighBracket(Some(c)) => {
+                write!(f, "expected ']' or ')' but found '{}'", c)
+            }
+            ParseIntervalError::InvalidHighBracket(None) => {
+                write!(f, "expected ']' or ')' but found end of input")
+            }
+            ParseIntervalError::MissingSeparator => write!(f, "expected ',' between bounds"),
+            ParseIntervalError::AmbiguousSeparator => {
+                write!(f, "expected a single ',' between bounds")
+            }
+            ParseIntervalError::ClosedInfinity => {
+                write!(f, "infinite bounds must use '(' or ')'")
+            }
+            ParseIntervalError::InvalidLow(e) => write!(f, "invalid lower bound: {}", e),
+            ParseIntervalError::InvalidHigh(e) => write!(f, "invalid upper bound: {}", e),
+            ParseIntervalError::Empty => write!(f, "lower bound is above upper bound"),
+        }
+    }
+}
+
+impl<E: Debug + Display> Error for ParseIntervalError<E> {}
+
+/// Parses an Interval written in mathematical notation
+///
+/// Infinite bounds are written as `-inf` and `+inf` (or `inf`) and must use
+/// an open bracket. The bounds are separated by a single `,`, so values whose
+/// text contains a comma can't be parsed and give `AmbiguousSeparator`.
+///
+/// # Example
+/// ```
+/// # use im_interval_tree::Interval;
+/// # use std::ops::Bound::*;
+/// let interval : Interval<u8> = "(7, 8]".parse().unwrap();
+/// assert_eq!(interval, Interval::new(Excluded(7), Included(8)));
+///
+/// let interval : Interval<u8> = "[5, +inf)".parse().unwrap();
+/// assert_eq!(interval, Interval::new(Included(5), Unbounded));
+///
+/// assert!("[5, 3]".parse::<Interval<u8>>().is_err());
+/// ```
+impl<T: Ord + Clone + FromStr, P: SharedPointerKind> FromStr for Interval<T, P> {
+    type Err = ParseIntervalError<T::Err>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let mut chars = s.chars();
+        let low_closed = match chars.next() {
+            Some('[') => true,
+            Some('(') => false,
+            c => return Err(ParseIntervalError::InvalidLowBracket(c)),
+        };
+        let high_closed = match chars.next_back() {
+            Some(']') => true,
+            Some(')') => false,
+            c => return Err(ParseIntervalError::InvalidHighBracket(c)),
+        };
+        let (low, high) = chars
+            .as_str()
+            .split_once(',')
+            .ok_or(ParseIntervalError::MissingSeparator)?;
+        if high.contains(',') {
+            return Err(ParseIntervalError::AmbiguousSeparator);
+        }
+
+        let low = match (low.trim(), low_closed) {
+            ("-inf", false) => Unbounded,
+            ("-inf", true) => return Err(ParseIntervalError::ClosedInfinity),
+            (low, closed) => {
+                let low = low.parse().map_err(ParseIntervalError::InvalidLow)?;
+                if closed {
+                    Included(low)
+                } else {
+                    Excluded(low)
+                }
+            }
+        };
+        let high = match (high.trim(), high_closed) {
+            ("+inf" | "inf", false) => Unbounded,
+            ("+inf" | "inf", true) => return Err(ParseIntervalError::ClosedInfinity),
+            (high, closed) => {
+                let high = high.parse().map_err(ParseIntervalError::InvalidHigh)?;
+                if closed {
+                    Included(high)
+                } else {
+                    Excluded(high)
+                }
+            }
+        };
+
+        let interval = Interval::new_with_ptr_kind(low, high);
+        if Self::valid(&interval) {
+            Ok(interval)
+        } else {
+            Err(ParseIntervalError::Empty)
+        }
+    }
+}
+
 #[cfg(test)]
-impl<T: Arbitrary + Clone + Ord + Debug> Arbitrary for Interval<T> {
+impl<T: Arbitrary + Clone + Ord + Debug, P: SharedPointerKind> Arbitrary for Interval<T, P> {
     fn arbitrary<G: Gen>(g: &mut G) -> Self {
         loop {
             let a = Bound::<T>::arbitrary(g);
             let b = Bound::<T>::arbitrary(g);
-            let interval = Interval::new(a.clone(), b.clone());
+            let interval = Interval::new_with_ptr_kind(a.clone(), b.clone());
             if Interval::valid(&interval) {
                 return interval;
             }
 
-            let interval = Interval::new(b.clone(), a.clone());
+            let interval = Interval::new_with_ptr_kind(b.clone(), a.clone());
             if Interval::valid(&interval) {
                 return interval;
             }
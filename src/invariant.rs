@@ -0,0 +1,142 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::ops::Bound;
+
+use crate::interval::*;
+use crate::shared::SharedPointerKind;
+use crate::Node;
+
+/// A structural invariant of an IntervalTree or IntervalBTree that does not hold
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvariantViolation {
+    /// An in-order traversal of the tree is not strictly increasing
+    Unordered,
+    /// A node's cached height does not match the height of its subtree
+    HeightMismatch { expected: usize, found: usize },
+    /// The heights of a node's subtrees differ by more than one
+    Unbalanced { balance_factor: isize },
+    /// A node's cached upper bound is not the maximum upper bound of its subtree
+    MaxMismatch,
+    /// A node's cached lower bound is not the minimum lower bound of its subtree
+    MinMismatch,
+    /// A stored interval is empty, with its low bound above its high bound
+    EmptyInterval,
+    /// A node of an IntervalBTree holds too few or too many entries
+    EntryCount { found: usize },
+    /// The leaves of an IntervalBTree are not all at the same depth
+    UnevenLeaves,
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvariantViolation::Unordered => write!(f, "intervals are not in sorted order"),
+            InvariantViolation::HeightMismatch { expected, found } => write!(
+                f,
+                "cached height is {} but subtree height is {}",
+                found, expected
+            ),
+            InvariantViolation::Unbalanced { balance_factor } => {
+                write!(f, "balance factor {} is out of range", balance_factor)
+            }
+            InvariantViolation::MaxMismatch => {
+                write!(f, "cached max does not match subtree maximum")
+            }
+            InvariantViolation::MinMismatch => {
+                write!(f, "cached min does not match subtree minimum")
+            }
+            InvariantViolation::EmptyInterval => write!(f, "a stored interval is empty"),
+            InvariantViolation::EntryCount { found } => {
+                write!(f, "node holds {} entries, which is out of range", found)
+            }
+            InvariantViolation::UnevenLeaves => write!(f, "leaves are not all at the same depth"),
+        }
+    }
+}
+
+impl Error for InvariantViolation {}
+
+/// The true properties of a subtree, recomputed without using any cached values
+struct Summary<'a, T: Ord + Clone, P: SharedPointerKind> {
+    height: usize,
+    first: &'a Interval<T, P>,
+    last: &'a Interval<T, P>,
+    max: &'a Bound<T>,
+    min: &'a Bound<T>,
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> Node<T, P> {
+    pub(crate) fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        self.summarize().map(|_| ())
+    }
+
+    fn summarize(&self) -> Result<Summary<'_, T, P>, InvariantViolation> {
+        if !Interval::valid(&self.interval) {
+            return Err(InvariantViolation::EmptyInterval);
+        }
+        let mut summary = Summary {
+            height: 1,
+            first: &self.interval,
+            last: &self.interval,
+            max: self.interval.high(),
+            min: self.interval.low(),
+        };
+        let mut left_height = 0;
+        let mut right_height = 0;
+
+        if let Some(left) = &self.left {
+            let left = left.summarize()?;
+            if left.last >= &self.interval {
+                return Err(InvariantViolation::Unordered);
+            }
+            left_height = left.height;
+            summary.first = left.first;
+            summary.max = max_high(summary.max, left.max);
+            summary.min = min_low(summary.min, left.min);
+        }
+        if let Some(right) = &self.right {
+            let right = right.summarize()?;
+            if right.first <= &self.interval {
+                return Err(InvariantViolation::Unordered);
+            }
+            right_height = right.height;
+            summary.last = right.last;
+            summary.max = max_high(summary.max, right.max);
+            summary.min = min_low(summary.min, right.min);
+        }
+
+        summary.height = usize::max(left_height, right_height) + 1;
+        if self.height != summary.height {
+            return Err(InvariantViolation::HeightMismatch {
+                expected: summary.height,
+                found: self.height,
+            });
+        }
+        let balance_factor = (left_height as isize) - (right_height as isize);
+        if balance_factor.abs() > 1 {
+            return Err(InvariantViolation::Unbalanced { balance_factor });
+        }
+        if *self.max != *summary.max {
+            return Err(InvariantViolation::MaxMismatch);
+        }
+        if *self.min != *summary.min {
+            return Err(InvariantViolation::MinMismatch);
+        }
+        Ok(summary)
+    }
+}
+
+fn max_high<'a, T: Ord + Clone>(a: &'a Bound<T>, b: &'a Bound<T>) -> &'a Bound<T> {
+    match high_bound_cmp(a, b) {
+        Ordering::Less => b,
+        _ => a,
+    }
+}
+
+fn min_low<'a, T: Ord + Clone>(a: &'a Bound<T>, b: &'a Bound<T>) -> &'a Bound<T> {
+    match low_bound_cmp(a, b) {
+        Ordering::Less => a,
+        _ => b,
+    }
+}
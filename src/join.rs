@@ -0,0 +1,114 @@
+use crate::shared::SharedPointerKind;
+use crate::{Interval, IntervalTree, Node};
+
+enum JoinTask<'a, T: Ord + Clone, P: SharedPointerKind> {
+    /// Every pair from a subtree of the left tree and a subtree of the right tree
+    Both(&'a Node<T, P>, &'a Node<T, P>),
+    /// A subtree of the left tree paired with a single interval from the right
+    Left(&'a Node<T, P>, &'a Interval<T, P>),
+    /// A single interval from the left tree paired with a subtree of the right
+    Right(&'a Interval<T, P>, &'a Node<T, P>),
+}
+
+/// An Iterator over pairs of overlapping intervals from two trees
+struct OverlapJoin<'a, T: Ord + Clone, P: SharedPointerKind> {
+    stack: Vec<JoinTask<'a, T, P>>,
+}
+
+impl<'a, T: Ord + Clone, P: SharedPointerKind> Iterator for OverlapJoin<'a, T, P> {
+    type Item = (Interval<T, P>, Interval<T, P>);
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(task) = self.stack.pop() {
+            match task {
+                JoinTask::Both(left, right) => {
+                    if !(left.max_reaches(&right.min) && left.min_reaches(&right.max)) {
+                        continue;
+                    }
+                    // Expand the taller subtree so both sides are pruned evenly
+                    if left.height >= right.height {
+                        self.stack.push(JoinTask::Right(&left.interval, right));
+                        for child in left.left.iter().chain(&left.right) {
+                            self.stack.push(JoinTask::Both(child, right));
+                        }
+                    } else {
+                        self.stack.push(JoinTask::Left(left, &right.interval));
+                        for child in right.left.iter().chain(&right.right) {
+                            self.stack.push(JoinTask::Both(left, child));
+                        }
+                    }
+                }
+                JoinTask::Left(node, probe) => {
+                    self.push_probes(node, probe, |child| JoinTask::Left(child, probe));
+                    if probe.overlaps(&node.interval) {
+                        return Some((node.interval.clone(), probe.clone()));
+                    }
+                }
+                JoinTask::Right(probe, node) => {
+                    self.push_probes(node, probe, |child| JoinTask::Right(probe, child));
+                    if probe.overlaps(&node.interval) {
+                        return Some((probe.clone(), node.interval.clone()));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<'a, T: Ord + Clone, P: SharedPointerKind> OverlapJoin<'a, T, P> {
+    fn push_probes<F>(&mut self, node: &'a Node<T, P>, probe: &Interval<T, P>, task: F)
+    where
+        F: Fn(&'a Node<T, P>) -> JoinTask<'a, T, P>,
+    {
+        if let Some(left_tree) = &node.left {
+            if left_tree.max_reaches(probe.low()) {
+                self.stack.push(task(left_tree))
+            }
+        }
+        if let Some(right_tree) = &node.right {
+            if right_tree.min_reaches(probe.high()) {
+                self.stack.push(task(right_tree))
+            }
+        }
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> IntervalTree<T, P> {
+    /// Return an Iterator over every pair of overlapping intervals, where the
+    /// first interval of each pair is from this tree and the second is from
+    /// `other`
+    ///
+    /// Both trees are descended together, so subtrees whose bounds cannot
+    /// overlap are skipped entirely rather than queried once per interval.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let genes : IntervalTree<u32> = IntervalTree::new();
+    /// let genes = genes.insert(Interval::new(Included(10), Excluded(20)));
+    /// let genes = genes.insert(Interval::new(Included(50), Excluded(60)));
+    ///
+    /// let reads : IntervalTree<u32> = IntervalTree::new();
+    /// let reads = reads.insert(Interval::new(Included(15), Excluded(25)));
+    /// let reads = reads.insert(Interval::new(Included(30), Excluded(40)));
+    ///
+    /// assert_eq!(
+    ///     genes.overlap_join(&reads).collect::<Vec<_>>(),
+    ///     vec![(
+    ///         Interval::new(Included(10), Excluded(20)),
+    ///         Interval::new(Included(15), Excluded(25)),
+    ///     )]
+    /// );
+    /// ```
+    pub fn overlap_join<'a>(
+        &'a self,
+        other: &'a IntervalTree<T, P>,
+    ) -> impl Iterator<Item = (Interval<T, P>, Interval<T, P>)> + 'a {
+        let mut stack = Vec::new();
+        if let (Some(left), Some(right)) = (&self.root, &other.root) {
+            stack.push(JoinTask::Both(&**left, &**right));
+        }
+        OverlapJoin { stack }
+    }
+}
@@ -34,35 +34,122 @@
  * ```
 */
 #[cfg(test)]
+#[allow(unused_mut, clippy::len_zero, clippy::needless_borrow, clippy::op_ref)]
 mod test;
 
 use std::cmp::*;
+use std::hash::{Hash, Hasher};
 use std::ops::Bound;
 use std::ops::Bound::*;
 
+mod batch;
+mod btree;
+mod cursor;
+mod diff;
 mod interval;
+mod invariant;
+mod join;
+mod locked;
+mod map;
+mod merge;
+mod nearest;
+mod observable;
+#[cfg(feature = "rayon")]
+mod par;
+mod range;
 mod shared;
+mod stats;
+mod versioned;
 
-pub use crate::interval::Interval;
+pub use crate::btree::IntervalBTree;
+pub use crate::cursor::Cursor;
+pub use crate::diff::Change;
 use crate::interval::*;
-use crate::shared::Shared;
+pub use crate::interval::{Interval, ParseIntervalError};
+pub use crate::invariant::InvariantViolation;
+pub use crate::locked::{AtomicIntervalTree, LockedIntervalTree};
+pub use crate::merge::{Conflict, Resolution};
+pub use crate::nearest::Measure;
+pub use crate::observable::{ObservableIntervalTree, SubscriptionId};
+pub use crate::shared::{
+    ArcK, DefaultSharedPointerKind, Inline, InlineArcK, InlineRcK, RcK, SharedPointerKind,
+};
+use crate::shared::{Shared, Stored};
+pub use crate::stats::{SharedStats, TreeStats};
+pub use crate::versioned::{Version, VersionedIntervalTree};
 
-#[derive(Clone, Hash)]
-struct Node<T: Ord + Clone> {
-    interval: Interval<T>,
-    left: Option<Shared<Node<T>>>,
-    right: Option<Shared<Node<T>>>,
+/// An IntervalTree that uses `Arc` pointers and can be sent between threads
+pub type IntervalTreeSync<T> = IntervalTree<T, ArcK>;
+
+/// An Interval that uses `Arc` pointers and can be sent between threads
+pub type IntervalSync<T> = Interval<T, ArcK>;
+
+/// A node on the path from the root of a tree, and the side of it the path
+/// continues down
+type Step<T, P> = (Shared<Node<T, P>, P>, Ordering);
+
+/// Reusable space for the path from the root that `insert` and `remove` walk
+///
+/// Updates walk down the tree iteratively rather than recursively, so their
+/// stack usage doesn't depend on the height of the tree. Passing the same
+/// buffer to `insert_with_buffer` or `remove_with_buffer` for many updates
+/// saves allocating a new path each time.
+pub struct PathBuffer<T: Ord + Clone, P: SharedPointerKind = DefaultSharedPointerKind> {
+    steps: Vec<Step<T, P>>,
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> PathBuffer<T, P> {
+    /// Construct an empty buffer
+    pub fn new() -> PathBuffer<T, P> {
+        PathBuffer { steps: Vec::new() }
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> Default for PathBuffer<T, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct Node<T: Ord + Clone, P: SharedPointerKind> {
+    interval: Interval<T, P>,
+    left: Option<Shared<Node<T, P>, P>>,
+    right: Option<Shared<Node<T, P>, P>>,
     height: usize,
-    max: Shared<Bound<T>>,
-    min: Shared<Bound<T>>,
+    max: Stored<Bound<T>, P>,
+    min: Stored<Bound<T>, P>,
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> Clone for Node<T, P> {
+    fn clone(&self) -> Self {
+        Node {
+            interval: self.interval.clone(),
+            left: self.left.clone(),
+            right: self.right.clone(),
+            height: self.height,
+            max: self.max.clone(),
+            min: self.min.clone(),
+        }
+    }
+}
+
+impl<T: Ord + Clone + Hash, P: SharedPointerKind> Hash for Node<T, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.interval.hash(state);
+        self.left.hash(state);
+        self.right.hash(state);
+        self.height.hash(state);
+        self.max.hash(state);
+        self.min.hash(state);
+    }
 }
 
-impl<T: Ord + Clone> Node<T> {
+impl<T: Ord + Clone, P: SharedPointerKind> Node<T, P> {
     fn new(
-        interval: Interval<T>,
-        left: Option<Shared<Node<T>>>,
-        right: Option<Shared<Node<T>>>,
-    ) -> Node<T> {
+        interval: Interval<T, P>,
+        left: Option<Shared<Node<T, P>, P>>,
+        right: Option<Shared<Node<T, P>, P>>,
+    ) -> Node<T, P> {
         let height = usize::max(Self::height(&left), Self::height(&right)) + 1;
         let max = Self::get_max(&interval, &left, &right);
         let min = Self::get_min(&interval, &left, &right);
@@ -76,11 +163,11 @@ impl<T: Ord + Clone> Node<T> {
         }
     }
 
-    fn leaf(interval: Interval<T>) -> Node<T> {
+    fn leaf(interval: Interval<T, P>) -> Node<T, P> {
         Node::new(interval, None, None)
     }
 
-    fn height(node: &Option<Shared<Node<T>>>) -> usize {
+    fn height(node: &Option<Shared<Node<T, P>, P>>) -> usize {
         match node {
             None => 0,
             Some(n) => n.height,
@@ -88,108 +175,148 @@ impl<T: Ord + Clone> Node<T> {
     }
 
     fn get_max(
-        interval: &Interval<T>,
-        left: &Option<Shared<Node<T>>>,
-        right: &Option<Shared<Node<T>>>,
-    ) -> Shared<Bound<T>> {
-        let mid = &interval.high;
-        match (left, right) {
-            (None, None) => mid.clone(),
-            (None, Some(r)) => high_bound_max(mid, &r.max),
-            (Some(l), None) => high_bound_max(mid, &l.max),
-            (Some(l), Some(r)) => high_bound_max(mid, &high_bound_max(&l.max, &r.max)),
+        interval: &Interval<T, P>,
+        left: &Option<Shared<Node<T, P>, P>>,
+        right: &Option<Shared<Node<T, P>, P>>,
+    ) -> Stored<Bound<T>, P> {
+        let mut max = &interval.high;
+        for child in left.iter().chain(right) {
+            if high_bound_cmp(&child.max, max) == Ordering::Greater {
+                max = &child.max;
+            }
         }
+        max.clone()
     }
 
     fn get_min(
-        interval: &Interval<T>,
-        left: &Option<Shared<Node<T>>>,
-        right: &Option<Shared<Node<T>>>,
-    ) -> Shared<Bound<T>> {
-        let mid = &interval.low;
-        match (left, right) {
-            (None, None) => mid.clone(),
-            (None, Some(r)) => low_bound_min(mid, &r.min),
-            (Some(l), None) => low_bound_min(mid, &l.min),
-            (Some(l), Some(r)) => low_bound_min(mid, &low_bound_min(&l.min, &r.min)),
+        interval: &Interval<T, P>,
+        left: &Option<Shared<Node<T, P>, P>>,
+        right: &Option<Shared<Node<T, P>, P>>,
+    ) -> Stored<Bound<T>, P> {
+        let mut min = &interval.low;
+        for child in left.iter().chain(right) {
+            if low_bound_cmp(&child.min, min) != Ordering::Greater {
+                min = &child.min;
+            }
         }
+        min.clone()
     }
 
     fn balance_factor(&self) -> isize {
         (Self::height(&self.left) as isize) - (Self::height(&self.right) as isize)
     }
 
-    fn insert(&self, interval: Interval<T>) -> Self {
-        let res = match interval.cmp(&self.interval) {
-            Ordering::Less => {
-                let insert_left = match &self.left {
-                    None => Node::leaf(interval),
-                    Some(left_tree) => left_tree.insert(interval),
-                };
-                Node::new(
-                    self.interval.clone(),
-                    Some(Shared::new(insert_left)),
-                    self.right.clone(),
-                )
-            }
-            Ordering::Greater => {
-                let insert_right = match &self.right {
-                    None => Node::leaf(interval),
-                    Some(right_tree) => right_tree.insert(interval),
-                };
-                Node::new(
-                    self.interval.clone(),
-                    self.left.clone(),
-                    Some(Shared::new(insert_right)),
-                )
+    /// Return the tree with the interval added, or `None` if it is already present
+    fn insert(
+        root: &Shared<Self, P>,
+        interval: Interval<T, P>,
+        path: &mut Vec<Step<T, P>>,
+    ) -> Option<Shared<Self, P>> {
+        path.clear();
+        path.reserve(root.height);
+        let mut node = root.clone();
+        loop {
+            let direction = interval.cmp(&node.interval);
+            let child = match direction {
+                Ordering::Less => node.left.clone(),
+                Ordering::Greater => node.right.clone(),
+                Ordering::Equal => {
+                    path.clear();
+                    return None;
+                }
+            };
+            path.push((node, direction));
+            match child {
+                None => break,
+                Some(child) => node = child,
             }
-            Ordering::Equal => self.clone(),
-        };
-        res.balance()
+        }
+        Self::rebuild(Some(Shared::new(Node::leaf(interval))), path, 0)
     }
 
-    fn get_minimum(&self) -> Interval<T> {
-        match &self.left {
-            None => self.interval.clone(),
-            Some(left_tree) => left_tree.get_minimum(),
-        }
-    }
-
-    fn remove(&self, interval: &Interval<T>) -> Option<Shared<Self>> {
-        let res = match interval.cmp(&self.interval) {
-            Ordering::Equal => match (&self.left, &self.right) {
-                (None, None) => None,
-                (Some(left_tree), None) => Some(left_tree.clone()),
-                (None, Some(right_tree)) => Some(right_tree.clone()),
-                (Some(_), Some(right_tree)) => {
-                    let successor = right_tree.get_minimum();
-                    let new_node = Node::new(
-                        successor.clone(),
-                        self.left.clone(),
-                        right_tree.remove(&successor),
-                    );
-                    Some(Shared::new(new_node))
+    fn get_minimum(&self) -> Interval<T, P> {
+        let mut node = self;
+        while let Some(left_tree) = &node.left {
+            node = left_tree;
+        }
+        node.interval.clone()
+    }
+
+    /// Return the tree with the interval removed along with the removed
+    /// interval, or `None` if it is not present
+    #[allow(clippy::type_complexity)]
+    fn remove(
+        root: &Shared<Self, P>,
+        interval: &Interval<T, P>,
+        path: &mut Vec<Step<T, P>>,
+    ) -> Option<(Option<Shared<Self, P>>, Interval<T, P>)> {
+        path.clear();
+        path.reserve(root.height);
+        let mut node = root.clone();
+        loop {
+            let direction = interval.cmp(&node.interval);
+            let child = match direction {
+                Ordering::Less => node.left.clone(),
+                Ordering::Greater => node.right.clone(),
+                Ordering::Equal => break,
+            };
+            match child {
+                None => {
+                    path.clear();
+                    return None;
                 }
-            },
-            Ordering::Less => match &self.left {
-                None => Some(Shared::new(self.clone())),
-                Some(left_tree) => Some(Shared::new(self.replace_left(left_tree.remove(interval)))),
-            },
-            Ordering::Greater => match &self.right {
-                None => Some(Shared::new(self.clone())),
-                Some(right_tree) => {
-                    Some(Shared::new(self.replace_right(right_tree.remove(interval))))
+                Some(child) => {
+                    path.push((node, direction));
+                    node = child;
                 }
-            },
+            }
+        }
+
+        let depth = path.len();
+        let subtree = match (&node.left, &node.right) {
+            (None, None) => None,
+            (Some(left_tree), None) => Some(left_tree.clone()),
+            (None, Some(right_tree)) => Some(right_tree.clone()),
+            (Some(_), Some(right_tree)) => {
+                // Unlink the successor from the right subtree and move it
+                // into the place of the removed node
+                let mut successor = right_tree.clone();
+                path.push((node.clone(), Ordering::Greater));
+                while let Some(left_tree) = successor.left.clone() {
+                    path.push((successor, Ordering::Less));
+                    successor = left_tree;
+                }
+                let new_right = Self::rebuild(successor.right.clone(), path, depth + 1);
+                path.pop();
+                let new_node = Node::new(successor.interval.clone(), node.left.clone(), new_right);
+                Some(Shared::new(new_node.balance()))
+            }
         };
-        res.map(|r| Shared::new(r.balance()))
+        Some((Self::rebuild(subtree, path, 0), node.interval.clone()))
+    }
+
+    /// Replace the subtree at the end of the path, copying and rebalancing
+    /// the nodes above it from the bottom up until `depth` steps are left
+    fn rebuild(
+        mut subtree: Option<Shared<Self, P>>,
+        path: &mut Vec<Step<T, P>>,
+        depth: usize,
+    ) -> Option<Shared<Self, P>> {
+        for (parent, direction) in path.drain(depth..).rev() {
+            let node = match direction {
+                Ordering::Less => parent.replace_left(subtree),
+                _ => parent.replace_right(subtree),
+            };
+            subtree = Some(Shared::new(node.balance()));
+        }
+        subtree
     }
 
-    fn replace_left(&self, new_left: Option<Shared<Node<T>>>) -> Node<T> {
+    fn replace_left(&self, new_left: Option<Shared<Node<T, P>, P>>) -> Node<T, P> {
         Self::new(self.interval.clone(), new_left, self.right.clone())
     }
 
-    fn replace_right(&self, new_right: Option<Shared<Node<T>>>) -> Node<T> {
+    fn replace_right(&self, new_right: Option<Shared<Node<T, P>, P>>) -> Node<T, P> {
         Self::new(self.interval.clone(), self.left.clone(), new_right)
     }
 
@@ -205,7 +332,7 @@ impl<T: Ord + Clone> Node<T> {
         pivot.replace_left(Some(Shared::new(new_left)))
     }
 
-    fn balance(&self) -> Self {
+    fn balance(self) -> Self {
         let balance_factor = self.balance_factor();
         if balance_factor < -1 {
             let right = self.right.as_ref().unwrap();
@@ -224,42 +351,254 @@ impl<T: Ord + Clone> Node<T> {
                 self.rotate_right()
             }
         } else {
-            self.clone()
+            self
+        }
+    }
+
+    /// Whether some interval in this subtree ends at or after the given low bound
+    fn max_reaches(&self, low: &Bound<T>) -> bool {
+        high_reaches(&self.max, low)
+    }
+
+    /// Whether some interval in this subtree starts at or before the given high bound
+    fn min_reaches(&self, high: &Bound<T>) -> bool {
+        low_reaches(&self.min, high)
+    }
+
+    /// Build a perfectly balanced tree from sorted, deduplicated intervals
+    fn from_sorted(intervals: &[Interval<T, P>]) -> Option<Shared<Node<T, P>, P>> {
+        if intervals.is_empty() {
+            return None;
+        }
+        let mid = intervals.len() / 2;
+        let left = Self::from_sorted(&intervals[..mid]);
+        let right = Self::from_sorted(&intervals[mid + 1..]);
+        Some(Shared::new(Node::new(intervals[mid].clone(), left, right)))
+    }
+
+    /// Join two trees with an interval that sorts between them
+    fn join(
+        left: Option<Shared<Node<T, P>, P>>,
+        interval: Interval<T, P>,
+        right: Option<Shared<Node<T, P>, P>>,
+    ) -> Node<T, P> {
+        let left_height = Self::height(&left);
+        let right_height = Self::height(&right);
+        if left_height > right_height + 1 {
+            let left = left.unwrap();
+            let new_right = Self::join(left.right.clone(), interval, right);
+            left.replace_right(Some(Shared::new(new_right))).balance()
+        } else if right_height > left_height + 1 {
+            let right = right.unwrap();
+            let new_left = Self::join(left, interval, right.left.clone());
+            right.replace_left(Some(Shared::new(new_left))).balance()
+        } else {
+            Node::new(interval, left, right)
+        }
+    }
+
+    /// Remove the least interval, returning the interval and the remaining tree
+    #[allow(clippy::type_complexity)]
+    fn split_first(&self) -> (Interval<T, P>, Option<Shared<Node<T, P>, P>>) {
+        match &self.left {
+            None => (self.interval.clone(), self.right.clone()),
+            Some(left_tree) => {
+                let (first, rest) = left_tree.split_first();
+                let joined = Self::join(rest, self.interval.clone(), self.right.clone());
+                (first, Some(Shared::new(joined)))
+            }
+        }
+    }
+
+    /// Remove the greatest interval, returning the remaining tree and the interval
+    #[allow(clippy::type_complexity)]
+    fn split_last(&self) -> (Option<Shared<Node<T, P>, P>>, Interval<T, P>) {
+        match &self.right {
+            None => (self.left.clone(), self.interval.clone()),
+            Some(right_tree) => {
+                let (rest, last) = right_tree.split_last();
+                let joined = Self::join(self.left.clone(), self.interval.clone(), rest);
+                (Some(Shared::new(joined)), last)
+            }
+        }
+    }
+
+    /// Split a tree into the intervals less than and greater than the given
+    /// interval, along with the interval itself if present
+    #[allow(clippy::type_complexity)]
+    fn split(
+        tree: &Option<Shared<Node<T, P>, P>>,
+        interval: &Interval<T, P>,
+    ) -> (
+        Option<Shared<Node<T, P>, P>>,
+        Option<Interval<T, P>>,
+        Option<Shared<Node<T, P>, P>>,
+    ) {
+        let node = match tree {
+            None => return (None, None, None),
+            Some(node) => node,
+        };
+        match interval.cmp(&node.interval) {
+            Ordering::Equal => (
+                node.left.clone(),
+                Some(node.interval.clone()),
+                node.right.clone(),
+            ),
+            Ordering::Less => {
+                let (less, found, greater) = Self::split(&node.left, interval);
+                let greater = Self::join(greater, node.interval.clone(), node.right.clone());
+                (less, found, Some(Shared::new(greater)))
+            }
+            Ordering::Greater => {
+                let (less, found, greater) = Self::split(&node.right, interval);
+                let less = Self::join(node.left.clone(), node.interval.clone(), less);
+                (Some(Shared::new(less)), found, greater)
+            }
+        }
+    }
+
+    /// Merge two trees, keeping one copy of any interval present in both
+    fn union(
+        a: &Option<Shared<Node<T, P>, P>>,
+        b: &Option<Shared<Node<T, P>, P>>,
+    ) -> Option<Shared<Node<T, P>, P>> {
+        match (a, b) {
+            (None, b) => b.clone(),
+            (a, None) => a.clone(),
+            (Some(a), b) => {
+                let (less, _, greater) = Self::split(b, &a.interval);
+                let left = Self::union(&a.left, &less);
+                let right = Self::union(&a.right, &greater);
+                Some(Shared::new(Self::join(left, a.interval.clone(), right)))
+            }
+        }
+    }
+
+    /// Concatenate two trees where every interval in `left` sorts before
+    /// every interval in `right`
+    fn join2(
+        left: Option<Shared<Node<T, P>, P>>,
+        right: Option<Shared<Node<T, P>, P>>,
+    ) -> Option<Shared<Node<T, P>, P>> {
+        match right {
+            None => left,
+            Some(right_tree) => {
+                let (first, rest) = right_tree.split_first();
+                Some(Shared::new(Self::join(left, first, rest)))
+            }
+        }
+    }
+
+    /// Whether two subtrees are the same allocation, or both empty
+    fn same_subtree(a: &Option<Shared<Node<T, P>, P>>, b: &Option<Shared<Node<T, P>, P>>) -> bool {
+        match (a, b) {
+            (None, None) => true,
+            (Some(a), Some(b)) => Shared::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    /// Keep only the intervals satisfying a predicate, reusing any subtree
+    /// whose intervals are all kept
+    fn filter<F>(tree: &Option<Shared<Node<T, P>, P>>, f: &mut F) -> Option<Shared<Node<T, P>, P>>
+    where
+        F: FnMut(&Interval<T, P>) -> bool,
+    {
+        let node = tree.as_ref()?;
+        let left = Self::filter(&node.left, f);
+        let keep = f(&node.interval);
+        let right = Self::filter(&node.right, f);
+        if !keep {
+            Self::join2(left, right)
+        } else if Self::same_subtree(&node.left, &left) && Self::same_subtree(&node.right, &right) {
+            tree.clone()
+        } else {
+            Some(Shared::new(Self::join(left, node.interval.clone(), right)))
+        }
+    }
+
+    /// Remove the intervals that overlap `window` and satisfy a predicate,
+    /// pushing them onto `removed` in sorted order
+    ///
+    /// Subtrees that cannot overlap the window are skipped without being
+    /// visited, and any subtree with nothing removed is reused.
+    fn remove_overlapping<F>(
+        tree: &Option<Shared<Node<T, P>, P>>,
+        window: &Interval<T, P>,
+        f: &F,
+        removed: &mut Vec<Interval<T, P>>,
+    ) -> Option<Shared<Node<T, P>, P>>
+    where
+        F: Fn(&Interval<T, P>) -> bool,
+    {
+        let node = match tree {
+            Some(node) if node.max_reaches(window.low()) && node.min_reaches(window.high()) => node,
+            _ => return tree.clone(),
+        };
+        let removed_before = removed.len();
+        let left = Self::remove_overlapping(&node.left, window, f, removed);
+        let remove = window.overlaps(&node.interval) && f(&node.interval);
+        if remove {
+            removed.push(node.interval.clone());
+        }
+        let right = Self::remove_overlapping(&node.right, window, f, removed);
+        if removed.len() == removed_before {
+            tree.clone()
+        } else if remove {
+            Self::join2(left, right)
+        } else {
+            Some(Shared::new(Self::join(left, node.interval.clone(), right)))
+        }
+    }
+
+    /// Split a tree into the intervals satisfying a predicate and the rest,
+    /// reusing any subtree whose intervals all end up on the same side
+    #[allow(clippy::type_complexity)]
+    fn partition<F>(
+        tree: &Option<Shared<Node<T, P>, P>>,
+        f: &mut F,
+    ) -> (Option<Shared<Node<T, P>, P>>, Option<Shared<Node<T, P>, P>>)
+    where
+        F: FnMut(&Interval<T, P>) -> bool,
+    {
+        let node = match tree {
+            None => return (None, None),
+            Some(node) => node,
+        };
+        let (left_in, left_out) = Self::partition(&node.left, f);
+        let keep = f(&node.interval);
+        let (right_in, right_out) = Self::partition(&node.right, f);
+        if keep && left_out.is_none() && right_out.is_none() {
+            (tree.clone(), None)
+        } else if !keep && left_in.is_none() && right_in.is_none() {
+            (None, tree.clone())
+        } else if keep {
+            let kept = Self::join(left_in, node.interval.clone(), right_in);
+            (Some(Shared::new(kept)), Self::join2(left_out, right_out))
+        } else {
+            let rejected = Self::join(left_out, node.interval.clone(), right_out);
+            (Self::join2(left_in, right_in), Some(Shared::new(rejected)))
         }
     }
 }
 
 /// An Iterator over Intervals matching some query
-pub struct Iter<T: Ord + Clone> {
-    stack: Vec<Shared<Node<T>>>,
-    query: Interval<T>,
+pub struct Iter<T: Ord + Clone, P: SharedPointerKind = DefaultSharedPointerKind> {
+    stack: Vec<Shared<Node<T, P>, P>>,
+    query: Interval<T, P>,
 }
 
-impl<T: Ord + Clone> Iterator for Iter<T> {
-    type Item = Interval<T>;
+impl<T: Ord + Clone, P: SharedPointerKind> Iterator for Iter<T, P> {
+    type Item = Interval<T, P>;
     fn next(&mut self) -> Option<Self::Item> {
         while let Some(node) = self.stack.pop() {
             if let Some(left_tree) = &node.left {
-                let max_is_gte = match (&*left_tree.max, self.query.low()) {
-                    (Included(max), Included(low)) => max >= low,
-                    (Included(max), Excluded(low))
-                    | (Excluded(max), Included(low))
-                    | (Excluded(max), Excluded(low)) => max > low,
-                    _ => true,
-                };
-                if max_is_gte {
+                if left_tree.max_reaches(self.query.low()) {
                     self.stack.push(left_tree.clone())
                 }
             }
             if let Some(right_tree) = &node.right {
-                let min_is_lte = match (&*right_tree.min, self.query.high()) {
-                    (Included(min), Included(high)) => min <= high,
-                    (Included(min), Excluded(high))
-                    | (Excluded(min), Included(high))
-                    | (Excluded(min), Excluded(high)) => min < high,
-                    _ => true,
-                };
-                if min_is_lte {
+                if right_tree.min_reaches(self.query.high()) {
                     self.stack.push(right_tree.clone())
                 }
             }
@@ -271,6 +610,34 @@ impl<T: Ord + Clone> Iterator for Iter<T> {
     }
 }
 
+/// An Iterator over references to Intervals matching some query
+pub struct IterRef<'a, T: Ord + Clone, P: SharedPointerKind = DefaultSharedPointerKind> {
+    stack: Vec<&'a Node<T, P>>,
+    query: Interval<T, P>,
+}
+
+impl<'a, T: Ord + Clone, P: SharedPointerKind> Iterator for IterRef<'a, T, P> {
+    type Item = &'a Interval<T, P>;
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.stack.pop() {
+            if let Some(left_tree) = &node.left {
+                if left_tree.max_reaches(self.query.low()) {
+                    self.stack.push(left_tree)
+                }
+            }
+            if let Some(right_tree) = &node.right {
+                if right_tree.min_reaches(self.query.high()) {
+                    self.stack.push(right_tree)
+                }
+            }
+            if self.query.overlaps(&node.interval) {
+                return Some(&node.interval);
+            }
+        }
+        None
+    }
+}
+
 /// An immutable data structure for storing and querying a collection of intervals
 ///
 /// # Example
@@ -305,19 +672,58 @@ impl<T: Ord + Clone> Iterator for Iter<T> {
 ///     ]
 /// );
 /// ```
-#[derive(Clone, Hash)]
-pub struct IntervalTree<T: Ord + Clone> {
-    root: Option<Shared<Node<T>>>,
+///
+/// Nodes are shared between trees using pointers of kind `P`, which defaults
+/// to `Rc` (or `Arc` with the `arc` feature). Use `IntervalTreeSync` for a tree
+/// that can be sent between threads regardless of features.
+pub struct IntervalTree<T: Ord + Clone, P: SharedPointerKind = DefaultSharedPointerKind> {
+    root: Option<Shared<Node<T, P>, P>>,
 }
 
 impl<T: Ord + Clone> IntervalTree<T> {
     /// Construct an empty IntervalTree
     pub fn new() -> IntervalTree<T> {
+        Self::new_with_ptr_kind()
+    }
+}
+
+impl<T: Ord + Clone> IntervalTree<T, ArcK> {
+    /// Construct an empty IntervalTree that uses `Arc` pointers
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTreeSync, IntervalSync};
+    /// let tree : IntervalTreeSync<u8> = IntervalTreeSync::new_sync();
+    /// let tree = tree.insert(IntervalSync::new_sync(Included(1), Included(2)));
+    ///
+    /// std::thread::spawn(move || assert_eq!(tree.iter().count(), 1))
+    ///     .join()
+    ///     .unwrap();
+    /// ```
+    pub fn new_sync() -> IntervalTree<T, ArcK> {
+        Self::new_with_ptr_kind()
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> IntervalTree<T, P> {
+    /// Construct an empty IntervalTree using any kind of shared pointer
+    ///
+    /// # Example
+    /// ```
+    /// # use im_interval_tree::{IntervalTree, RcK};
+    /// let tree : IntervalTree<u8, RcK> = IntervalTree::new_with_ptr_kind();
+    /// assert_eq!(tree.iter().count(), 0);
+    /// ```
+    pub fn new_with_ptr_kind() -> IntervalTree<T, P> {
         IntervalTree { root: None }
     }
 
     /// Construct a new IntervalTree with the given Interval added
     ///
+    /// If the interval is already present or is empty, the returned tree
+    /// shares its root with this one.
+    ///
     /// # Example
     /// ```
     /// # use std::ops::Bound::*;
@@ -329,18 +735,81 @@ impl<T: Ord + Clone> IntervalTree<T> {
     ///     vec![Interval::new(Included(1), Included(2))]
     /// );
     /// ```
-    pub fn insert(&self, interval: Interval<T>) -> IntervalTree<T> {
-        let new_root = match &self.root {
-            None => Node::leaf(interval),
-            Some(node) => node.insert(interval),
-        };
-        IntervalTree {
-            root: Some(Shared::new(new_root)),
+    pub fn insert(&self, interval: Interval<T, P>) -> IntervalTree<T, P> {
+        self.insert_full(interval).0
+    }
+
+    /// Construct a new IntervalTree with the given Interval added, along with
+    /// whether the interval was newly added
+    ///
+    /// If the interval is already present or is empty, nothing is added and
+    /// the returned tree shares its root with this one.
+    ///
+    /// This allocates space for the path from the root on each call. Use
+    /// `insert_with_buffer` to reuse that space across many insertions.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let (tree, added) = tree.insert_full(Interval::new(Included(1), Included(2)));
+    /// assert!(added);
+    ///
+    /// let (same, added) = tree.insert_full(Interval::new(Included(1), Included(2)));
+    /// assert!(!added);
+    /// assert!(same.ptr_eq(&tree));
+    ///
+    /// let (same, added) = tree.insert_full(Interval::new(Included(5), Excluded(2)));
+    /// assert!(!added);
+    /// assert!(same.ptr_eq(&tree));
+    /// ```
+    pub fn insert_full(&self, interval: Interval<T, P>) -> (IntervalTree<T, P>, bool) {
+        self.insert_with_buffer(interval, &mut PathBuffer::new())
+    }
+
+    /// Construct a new IntervalTree with the given Interval added, along with
+    /// whether the interval was newly added, using the given buffer to hold
+    /// the path from the root
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval, PathBuffer};
+    /// let mut buffer = PathBuffer::new();
+    /// let mut tree : IntervalTree<u8> = IntervalTree::new();
+    /// for i in 0..100 {
+    ///     tree = tree
+    ///         .insert_with_buffer(Interval::new(Included(i), Excluded(i + 1)), &mut buffer)
+    ///         .0;
+    /// }
+    /// assert_eq!(tree.iter().count(), 100);
+    /// ```
+    pub fn insert_with_buffer(
+        &self,
+        interval: Interval<T, P>,
+        buffer: &mut PathBuffer<T, P>,
+    ) -> (IntervalTree<T, P>, bool) {
+        if !Interval::valid(&interval) {
+            return (self.clone(), false);
         }
+        let root = match &self.root {
+            None => Shared::new(Node::leaf(interval)),
+            Some(node) => match Node::insert(node, interval, &mut buffer.steps) {
+                None => return (self.clone(), false),
+                Some(new_root) => new_root,
+            },
+        };
+        let tree = IntervalTree { root: Some(root) };
+        tree.debug_check_invariants();
+        (tree, true)
     }
 
     /// Construct a new IntervalTree minus the given Interval, if present
     ///
+    /// If the interval is not present, the returned tree shares its root with
+    /// this one.
+    ///
     /// # Example
     /// ```
     /// # use std::ops::Bound::*;
@@ -355,12 +824,77 @@ impl<T: Ord + Clone> IntervalTree<T> {
     ///     vec![Interval::new(Included(1), Included(3))]
     /// );
     /// ```
-    pub fn remove(&self, interval: &Interval<T>) -> IntervalTree<T> {
-        match &self.root {
-            None => IntervalTree::new(),
-            Some(node) => IntervalTree {
-                root: node.remove(interval),
-            },
+    pub fn remove(&self, interval: &Interval<T, P>) -> IntervalTree<T, P> {
+        self.remove_full(interval).0
+    }
+
+    /// Construct a new IntervalTree minus the given Interval, along with the
+    /// removed interval if it was present
+    ///
+    /// If the interval is not present, the returned tree shares its root with
+    /// this one.
+    ///
+    /// This allocates space for the path from the root on each call. Use
+    /// `remove_with_buffer` to reuse that space across many removals.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Included(2)));
+    ///
+    /// let (smaller, removed) = tree.remove_full(&Interval::new(Included(1), Included(2)));
+    /// assert_eq!(removed, Some(Interval::new(Included(1), Included(2))));
+    /// assert_eq!(smaller.iter().count(), 0);
+    ///
+    /// let (same, removed) = tree.remove_full(&Interval::new(Included(5), Included(6)));
+    /// assert_eq!(removed, None);
+    /// assert!(same.ptr_eq(&tree));
+    /// ```
+    pub fn remove_full(
+        &self,
+        interval: &Interval<T, P>,
+    ) -> (IntervalTree<T, P>, Option<Interval<T, P>>) {
+        self.remove_with_buffer(interval, &mut PathBuffer::new())
+    }
+
+    /// Construct a new IntervalTree minus the given Interval, along with the
+    /// removed interval if it was present, using the given buffer to hold the
+    /// path from the root
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval, PathBuffer};
+    /// let mut tree : IntervalTree<u8> = (0..100)
+    ///     .map(|i| Interval::new(Included(i), Excluded(i + 1)))
+    ///     .collect();
+    ///
+    /// let mut buffer = PathBuffer::new();
+    /// for i in (0..100).step_by(2) {
+    ///     tree = tree
+    ///         .remove_with_buffer(&Interval::new(Included(i), Excluded(i + 1)), &mut buffer)
+    ///         .0;
+    /// }
+    /// assert_eq!(tree.iter().count(), 50);
+    /// ```
+    pub fn remove_with_buffer(
+        &self,
+        interval: &Interval<T, P>,
+        buffer: &mut PathBuffer<T, P>,
+    ) -> (IntervalTree<T, P>, Option<Interval<T, P>>) {
+        let removed = self
+            .root
+            .as_ref()
+            .and_then(|node| Node::remove(node, interval, &mut buffer.steps));
+        match removed {
+            None => (self.clone(), None),
+            Some((root, removed)) => {
+                let tree = IntervalTree { root };
+                tree.debug_check_invariants();
+                (tree, Some(removed))
+            }
         }
     }
 
@@ -381,7 +915,10 @@ impl<T: Ord + Clone> IntervalTree<T> {
     ///     vec![Interval::new(Included(5), Unbounded)]
     /// );
     /// ```
-    pub fn query_interval(&self, interval: &Interval<T>) -> impl Iterator<Item = Interval<T>> + '_ {
+    pub fn query_interval(
+        &self,
+        interval: &Interval<T, P>,
+    ) -> impl Iterator<Item = Interval<T, P>> + '_ {
         let mut stack = Vec::new();
         if let Some(node) = &self.root {
             stack.push(node.clone())
@@ -411,8 +948,9 @@ impl<T: Ord + Clone> IntervalTree<T> {
     ///     vec![Interval::new(Included(1), Excluded(3))]
     /// );
     /// ```
-    pub fn query_point(&self, point: &T) -> impl Iterator<Item = Interval<T>> + '_ {
-        let interval = Interval::new(Included(point.clone()), Included(point.clone()));
+    pub fn query_point(&self, point: &T) -> impl Iterator<Item = Interval<T, P>> + '_ {
+        let interval =
+            Interval::new_with_ptr_kind(Included(point.clone()), Included(point.clone()));
         self.query_interval(&interval)
     }
 
@@ -437,13 +975,630 @@ impl<T: Ord + Clone> IntervalTree<T> {
     ///     ]
     /// );
     /// ```
-    pub fn iter(&self) -> impl Iterator<Item = Interval<T>> + '_ {
-        self.query_interval(&Interval::new(Unbounded, Unbounded))
+    pub fn iter(&self) -> impl Iterator<Item = Interval<T, P>> + '_ {
+        self.query_interval(&Interval::new_with_ptr_kind(Unbounded, Unbounded))
     }
-}
 
-impl<T: Ord + Clone> Default for IntervalTree<T> {
-    fn default() -> Self {
-        Self::new()
+    /// Return an Iterator over references to all the intervals in the tree
+    /// that overlap with the given interval
+    ///
+    /// Unlike `query_interval`, this does not clone the matching intervals.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<String> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included("a".to_string()), Excluded("c".to_string())));
+    /// let tree = tree.insert(Interval::new(Included("e".to_string()), Unbounded));
+    ///
+    /// let query = Interval::new(Included("c".to_string()), Included("f".to_string()));
+    /// assert_eq!(
+    ///     tree.query_interval_ref(&query).collect::<Vec<&Interval<String>>>(),
+    ///     vec![&Interval::new(Included("e".to_string()), Unbounded)]
+    /// );
+    /// ```
+    pub fn query_interval_ref(&self, interval: &Interval<T, P>) -> IterRef<'_, T, P> {
+        let mut stack = Vec::new();
+        if let Some(node) = &self.root {
+            stack.push(&**node)
+        }
+        IterRef {
+            stack,
+            query: interval.clone(),
+        }
+    }
+
+    /// Return an Iterator over references to all the intervals in the tree
+    /// that contain the given point
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    ///
+    /// assert_eq!(
+    ///     tree.query_point_ref(&2).collect::<Vec<&Interval<u8>>>(),
+    ///     vec![&Interval::new(Included(1), Excluded(3))]
+    /// );
+    /// ```
+    pub fn query_point_ref(&self, point: &T) -> IterRef<'_, T, P> {
+        let interval =
+            Interval::new_with_ptr_kind(Included(point.clone()), Included(point.clone()));
+        self.query_interval_ref(&interval)
+    }
+
+    /// Return an Iterator over references to all the intervals in the tree
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(2), Excluded(4)));
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    ///
+    /// assert_eq!(
+    ///     tree.iter_ref().collect::<Vec<&Interval<u8>>>(),
+    ///     vec![
+    ///         &Interval::new(Included(2), Excluded(4)),
+    ///         &Interval::new(Included(5), Unbounded),
+    ///     ]
+    /// );
+    /// ```
+    pub fn iter_ref(&self) -> IterRef<'_, T, P> {
+        self.query_interval_ref(&Interval::new_with_ptr_kind(Unbounded, Unbounded))
+    }
+
+    /// Verify the structural invariants of the tree
+    ///
+    /// This checks that every interval is non-empty and the intervals are in
+    /// sorted order, that the tree is AVL-balanced with correct cached
+    /// heights, and that the cached bounds of every subtree match its true
+    /// extremes. A tree built only through the public API from non-empty
+    /// intervals should always pass.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    ///
+    /// assert_eq!(tree.check_invariants(), Ok(()));
+    /// ```
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        match &self.root {
+            None => Ok(()),
+            Some(node) => node.check_invariants(),
+        }
+    }
+
+    /// Check whether the tree contains the given interval
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    ///
+    /// assert!(tree.contains(&Interval::new(Included(1), Excluded(3))));
+    /// assert!(!tree.contains(&Interval::new(Included(1), Included(3))));
+    /// ```
+    pub fn contains(&self, interval: &Interval<T, P>) -> bool {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            current = match interval.cmp(&node.interval) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return true,
+            };
+        }
+        false
+    }
+
+    /// Return the least interval in the tree
+    ///
+    /// Intervals are ordered by their lower bound, then by their upper bound.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(2), Excluded(4)));
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(5)));
+    ///
+    /// assert_eq!(tree.first(), Some(Interval::new(Included(1), Excluded(5))));
+    /// ```
+    pub fn first(&self) -> Option<Interval<T, P>> {
+        self.root.as_ref().map(|node| node.get_minimum())
+    }
+
+    /// Return the greatest interval in the tree
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(2), Excluded(4)));
+    /// let tree = tree.insert(Interval::new(Included(2), Excluded(3)));
+    ///
+    /// assert_eq!(tree.last(), Some(Interval::new(Included(2), Excluded(4))));
+    /// ```
+    pub fn last(&self) -> Option<Interval<T, P>> {
+        let mut node = self.root.as_ref()?;
+        while let Some(right_tree) = &node.right {
+            node = right_tree;
+        }
+        Some(node.interval.clone())
+    }
+
+    /// Return the greatest interval in the tree that is less than the given
+    /// interval, which need not be in the tree itself
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    ///
+    /// assert_eq!(
+    ///     tree.predecessor(&Interval::new(Included(5), Unbounded)),
+    ///     Some(Interval::new(Included(1), Excluded(3)))
+    /// );
+    /// assert_eq!(tree.predecessor(&Interval::new(Included(1), Excluded(3))), None);
+    /// ```
+    pub fn predecessor(&self, interval: &Interval<T, P>) -> Option<Interval<T, P>> {
+        let mut best = None;
+        let mut current = &self.root;
+        while let Some(node) = current {
+            if node.interval < *interval {
+                best = Some(&node.interval);
+                current = &node.right;
+            } else {
+                current = &node.left;
+            }
+        }
+        best.cloned()
+    }
+
+    /// Return the least interval in the tree that is greater than the given
+    /// interval, which need not be in the tree itself
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    ///
+    /// assert_eq!(
+    ///     tree.successor(&Interval::new(Included(2), Included(2))),
+    ///     Some(Interval::new(Included(5), Unbounded))
+    /// );
+    /// assert_eq!(tree.successor(&Interval::new(Included(5), Unbounded)), None);
+    /// ```
+    pub fn successor(&self, interval: &Interval<T, P>) -> Option<Interval<T, P>> {
+        let mut best = None;
+        let mut current = &self.root;
+        while let Some(node) = current {
+            if node.interval > *interval {
+                best = Some(&node.interval);
+                current = &node.left;
+            } else {
+                current = &node.right;
+            }
+        }
+        best.cloned()
+    }
+
+    /// Remove the least interval, returning it along with the new IntervalTree
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    ///
+    /// let (first, tree) = tree.pop_first().unwrap();
+    /// assert_eq!(first, Interval::new(Included(1), Excluded(3)));
+    /// assert_eq!(
+    ///     tree.iter().collect::<Vec<Interval<u8>>>(),
+    ///     vec![Interval::new(Included(5), Unbounded)]
+    /// );
+    /// ```
+    pub fn pop_first(&self) -> Option<(Interval<T, P>, IntervalTree<T, P>)> {
+        let (first, rest) = self.root.as_ref()?.split_first();
+        let tree = IntervalTree { root: rest };
+        tree.debug_check_invariants();
+        Some((first, tree))
+    }
+
+    /// Remove the greatest interval, returning it along with the new IntervalTree
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    ///
+    /// let (last, tree) = tree.pop_last().unwrap();
+    /// assert_eq!(last, Interval::new(Included(5), Unbounded));
+    /// assert_eq!(
+    ///     tree.iter().collect::<Vec<Interval<u8>>>(),
+    ///     vec![Interval::new(Included(1), Excluded(3))]
+    /// );
+    /// ```
+    pub fn pop_last(&self) -> Option<(Interval<T, P>, IntervalTree<T, P>)> {
+        let (rest, last) = self.root.as_ref()?.split_last();
+        let tree = IntervalTree { root: rest };
+        tree.debug_check_invariants();
+        Some((last, tree))
+    }
+
+    /// Construct a new IntervalTree containing the intervals of both trees
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let a : IntervalTree<u8> = IntervalTree::new();
+    /// let a = a.insert(Interval::new(Included(1), Excluded(3)));
+    /// let b : IntervalTree<u8> = IntervalTree::new();
+    /// let b = b.insert(Interval::new(Included(5), Unbounded));
+    ///
+    /// assert_eq!(
+    ///     a.union(&b).iter().collect::<Vec<Interval<u8>>>(),
+    ///     vec![
+    ///         Interval::new(Included(1), Excluded(3)),
+    ///         Interval::new(Included(5), Unbounded),
+    ///     ]
+    /// );
+    /// ```
+    pub fn union(&self, other: &Self) -> IntervalTree<T, P> {
+        let tree = IntervalTree {
+            root: Node::union(&self.root, &other.root),
+        };
+        tree.debug_check_invariants();
+        tree
+    }
+
+    /// Construct a new IntervalTree containing only the intervals for which
+    /// the predicate returns true
+    ///
+    /// The predicate is called once per interval, in sorted order. This takes
+    /// linear time, and any subtree whose intervals are all kept is shared
+    /// with this tree rather than copied.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    ///
+    /// let bounded = tree.filter(|i| *i.high() != Unbounded);
+    /// assert_eq!(
+    ///     bounded.iter().collect::<Vec<Interval<u8>>>(),
+    ///     vec![Interval::new(Included(1), Excluded(3))]
+    /// );
+    /// assert!(tree.filter(|_| true).ptr_eq(&tree));
+    /// ```
+    pub fn filter<F>(&self, mut f: F) -> IntervalTree<T, P>
+    where
+        F: FnMut(&Interval<T, P>) -> bool,
+    {
+        let tree = IntervalTree {
+            root: Node::filter(&self.root, &mut f),
+        };
+        tree.debug_check_invariants();
+        tree
+    }
+
+    /// Keep only the intervals for which the predicate returns true
+    ///
+    /// This replaces the tree with `self.filter(f)`, so other versions of the
+    /// tree are unaffected.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let mut tree : IntervalTree<u8> = IntervalTree::new();
+    /// tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// tree = tree.insert(Interval::new(Included(5), Unbounded));
+    /// let before = tree.clone();
+    ///
+    /// tree.retain(|i| *i.high() == Unbounded);
+    /// assert_eq!(tree.iter().count(), 1);
+    /// assert_eq!(before.iter().count(), 2);
+    /// ```
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Interval<T, P>) -> bool,
+    {
+        *self = self.filter(f);
+    }
+
+    /// Split the tree into the intervals for which the predicate returns true
+    /// and those for which it returns false
+    ///
+    /// The predicate is called once per interval, in sorted order. This takes
+    /// linear time, and any subtree whose intervals all end up in the same
+    /// tree is shared with this tree rather than copied.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    ///
+    /// let (bounded, unbounded) = tree.partition(|i| *i.high() != Unbounded);
+    /// assert_eq!(
+    ///     bounded.iter().collect::<Vec<Interval<u8>>>(),
+    ///     vec![Interval::new(Included(1), Excluded(3))]
+    /// );
+    /// assert_eq!(
+    ///     unbounded.iter().collect::<Vec<Interval<u8>>>(),
+    ///     vec![Interval::new(Included(5), Unbounded)]
+    /// );
+    /// ```
+    pub fn partition<F>(&self, mut f: F) -> (IntervalTree<T, P>, IntervalTree<T, P>)
+    where
+        F: FnMut(&Interval<T, P>) -> bool,
+    {
+        let (kept, rejected) = Node::partition(&self.root, &mut f);
+        let kept = IntervalTree { root: kept };
+        let rejected = IntervalTree { root: rejected };
+        kept.debug_check_invariants();
+        rejected.debug_check_invariants();
+        (kept, rejected)
+    }
+
+    /// Construct a new IntervalTree minus every interval that overlaps with
+    /// the given interval, along with the removed intervals in sorted order
+    ///
+    /// Only the parts of the tree that can overlap the window are visited, and
+    /// the rest is shared with this tree.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Included(2), Excluded(4)));
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    ///
+    /// let (tree, removed) = tree.remove_overlapping(&Interval::new(Included(3), Included(6)));
+    /// assert_eq!(
+    ///     removed,
+    ///     vec![
+    ///         Interval::new(Included(2), Excluded(4)),
+    ///         Interval::new(Included(5), Unbounded),
+    ///     ]
+    /// );
+    /// assert_eq!(
+    ///     tree.iter().collect::<Vec<Interval<u8>>>(),
+    ///     vec![Interval::new(Included(1), Excluded(3))]
+    /// );
+    /// ```
+    pub fn remove_overlapping(
+        &self,
+        interval: &Interval<T, P>,
+    ) -> (IntervalTree<T, P>, Vec<Interval<T, P>>) {
+        self.remove_overlapping_where(interval, |_| true)
+    }
+
+    /// Construct a new IntervalTree minus every interval that is contained in
+    /// the given interval, along with the removed intervals in sorted order
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Included(2), Excluded(4)));
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    ///
+    /// let (tree, removed) = tree.remove_contained_in(&Interval::new(Included(2), Included(6)));
+    /// assert_eq!(removed, vec![Interval::new(Included(2), Excluded(4))]);
+    /// assert_eq!(tree.iter().count(), 2);
+    /// ```
+    pub fn remove_contained_in(
+        &self,
+        interval: &Interval<T, P>,
+    ) -> (IntervalTree<T, P>, Vec<Interval<T, P>>) {
+        self.remove_overlapping_where(interval, |i| interval.contains(i))
+    }
+
+    /// Construct a new IntervalTree from the overlap of each interval with the
+    /// given window, dropping intervals that do not overlap it
+    ///
+    /// Intervals that become equal once clipped are only kept once.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Included(2), Excluded(4)));
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    ///
+    /// let clipped = tree.clip(&Interval::new(Included(3), Included(6)));
+    /// let mut clipped = clipped.iter().collect::<Vec<Interval<u8>>>();
+    /// clipped.sort();
+    /// assert_eq!(
+    ///     clipped,
+    ///     vec![
+    ///         Interval::new(Included(3), Excluded(4)),
+    ///         Interval::new(Included(5), Included(6)),
+    ///     ]
+    /// );
+    /// ```
+    pub fn clip(&self, window: &Interval<T, P>) -> IntervalTree<T, P> {
+        self.query_interval(window)
+            .filter_map(|interval| interval.get_overlap(window))
+            .collect()
+    }
+
+    /// Construct a new IntervalTree with every interval cut to start no
+    /// earlier than the given point, dropping intervals that end before it
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Included(2), Excluded(4)));
+    ///
+    /// assert_eq!(
+    ///     tree.truncate_before(&3).iter().collect::<Vec<Interval<u8>>>(),
+    ///     vec![Interval::new(Included(3), Excluded(4))]
+    /// );
+    /// ```
+    pub fn truncate_before(&self, point: &T) -> IntervalTree<T, P> {
+        self.clip(&Interval::new_with_ptr_kind(
+            Included(point.clone()),
+            Unbounded,
+        ))
+    }
+
+    /// Construct a new IntervalTree with every interval cut to end no later
+    /// than the given point, dropping intervals that start after it
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Excluded(2), Excluded(4)));
+    ///
+    /// assert_eq!(
+    ///     tree.truncate_after(&2).iter().collect::<Vec<Interval<u8>>>(),
+    ///     vec![Interval::new(Included(1), Included(2))]
+    /// );
+    /// ```
+    pub fn truncate_after(&self, point: &T) -> IntervalTree<T, P> {
+        self.clip(&Interval::new_with_ptr_kind(
+            Unbounded,
+            Included(point.clone()),
+        ))
+    }
+
+    fn remove_overlapping_where<F>(
+        &self,
+        window: &Interval<T, P>,
+        f: F,
+    ) -> (IntervalTree<T, P>, Vec<Interval<T, P>>)
+    where
+        F: Fn(&Interval<T, P>) -> bool,
+    {
+        let mut removed = Vec::new();
+        let tree = IntervalTree {
+            root: Node::remove_overlapping(&self.root, window, &f, &mut removed),
+        };
+        tree.debug_check_invariants();
+        (tree, removed)
+    }
+
+    /// Check whether two trees share the same root
+    ///
+    /// Trees that are pointer-equal are guaranteed to contain the same
+    /// intervals. This is a constant time check, so it is useful for cheaply
+    /// detecting whether a tree has changed.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let other = tree.insert(Interval::new(Included(5), Unbounded));
+    ///
+    /// assert!(tree.ptr_eq(&tree.clone()));
+    /// assert!(!tree.ptr_eq(&other));
+    /// ```
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Node::same_subtree(&self.root, &other.root)
+    }
+
+    #[inline]
+    fn debug_check_invariants(&self) {
+        if cfg!(feature = "debug-invariants") {
+            if let Err(violation) = self.check_invariants() {
+                panic!("IntervalTree invariant violated: {}", violation);
+            }
+        }
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> Clone for IntervalTree<T, P> {
+    fn clone(&self) -> Self {
+        IntervalTree {
+            root: self.root.clone(),
+        }
+    }
+}
+
+impl<T: Ord + Clone + Hash, P: SharedPointerKind> Hash for IntervalTree<T, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.root.hash(state)
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> Default for IntervalTree<T, P> {
+    fn default() -> Self {
+        Self::new_with_ptr_kind()
+    }
+}
+
+/// Builds a balanced IntervalTree in a single pass after sorting, skipping
+/// empty intervals
+///
+/// # Example
+/// ```
+/// # use std::ops::Bound::*;
+/// # use im_interval_tree::{IntervalTree, Interval};
+/// let tree : IntervalTree<u8> = vec![
+///     Interval::new(Included(5), Unbounded),
+///     Interval::new(Included(1), Excluded(3)),
+/// ]
+/// .into_iter()
+/// .collect();
+///
+/// assert_eq!(tree.iter().count(), 2);
+/// ```
+impl<T: Ord + Clone, P: SharedPointerKind> FromIterator<Interval<T, P>> for IntervalTree<T, P> {
+    fn from_iter<I: IntoIterator<Item = Interval<T, P>>>(iter: I) -> Self {
+        let mut intervals = iter.into_iter().filter(Interval::valid).collect::<Vec<_>>();
+        intervals.sort_unstable();
+        intervals.dedup();
+        let tree = IntervalTree {
+            root: Node::from_sorted(&intervals),
+        };
+        tree.debug_check_invariants();
+        tree
     }
 }
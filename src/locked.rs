@@ -0,0 +1,153 @@
+use std::sync::{PoisonError, RwLock};
+
+use crate::shared::{ArcK, SharedPointerKind};
+use crate::{Interval, IntervalTree};
+
+/// A thread-safe handle to the current version of an IntervalTree
+///
+/// Readers take cheap snapshots of the current version with `load`, which stay
+/// consistent no matter what writers do afterwards. Writers build a new
+/// version from a snapshot and publish it with `compare_and_swap`, or use
+/// `update` to retry automatically when another writer got there first.
+///
+/// The current version is guarded by a `RwLock`, so this is not lock-free:
+/// `load` waits while a writer is publishing. The lock is only held to clone
+/// or replace the root pointer, never while a new version is being built, so
+/// waits are short.
+///
+/// Any pointer kind can be used, and the handle can be shared between threads
+/// when the kind's pointers are `Send + Sync`, as with `ArcK` (the default) and
+/// `InlineArcK`.
+///
+/// # Example
+/// ```
+/// # use std::ops::Bound::*;
+/// # use im_interval_tree::{IntervalSync, LockedIntervalTree};
+/// let handle : LockedIntervalTree<u8> = LockedIntervalTree::new();
+/// let before = handle.load();
+///
+/// handle.update(|tree| {
+///     tree.insert(IntervalSync::new_sync(Included(1), Excluded(3)))
+///         .insert(IntervalSync::new_sync(Included(5), Unbounded))
+/// });
+///
+/// assert_eq!(before.iter().count(), 0);
+/// assert_eq!(handle.load().iter().count(), 2);
+/// ```
+pub struct LockedIntervalTree<T: Ord + Clone, P: SharedPointerKind = ArcK> {
+    current: RwLock<IntervalTree<T, P>>,
+}
+
+/// The name this handle was first requested under, kept for callers that use
+/// it even though publishing goes through a lock rather than an atomic
+///
+/// # Example
+/// ```
+/// # use std::ops::Bound::*;
+/// # use im_interval_tree::{AtomicIntervalTree, IntervalSync};
+/// let handle : AtomicIntervalTree<u8> = AtomicIntervalTree::new();
+/// handle.insert(IntervalSync::new_sync(Included(1), Excluded(3)));
+/// assert_eq!(handle.load().iter().count(), 1);
+/// ```
+pub type AtomicIntervalTree<T, P = ArcK> = LockedIntervalTree<T, P>;
+
+impl<T: Ord + Clone> LockedIntervalTree<T> {
+    /// Construct a handle to an empty IntervalTree
+    pub fn new() -> LockedIntervalTree<T> {
+        Self::from_tree(IntervalTree::new_sync())
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> LockedIntervalTree<T, P> {
+    /// Construct a handle whose current version is the given tree
+    pub fn from_tree(tree: IntervalTree<T, P>) -> LockedIntervalTree<T, P> {
+        LockedIntervalTree {
+            current: RwLock::new(tree),
+        }
+    }
+
+    /// Return a snapshot of the current version
+    pub fn load(&self) -> IntervalTree<T, P> {
+        self.current
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replace the current version, returning the previous one
+    pub fn swap(&self, tree: IntervalTree<T, P>) -> IntervalTree<T, P> {
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        std::mem::replace(&mut *current, tree)
+    }
+
+    /// Replace the current version with `new` only if it is still `expected`
+    ///
+    /// Versions are compared by pointer, so `expected` should be a snapshot
+    /// previously returned by `load`. On failure, the actual current version
+    /// is returned so the caller can retry against it.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalSync, LockedIntervalTree};
+    /// let handle : LockedIntervalTree<u8> = LockedIntervalTree::new();
+    /// let snapshot = handle.load();
+    /// let new = snapshot.insert(IntervalSync::new_sync(Included(1), Excluded(3)));
+    ///
+    /// assert!(handle.compare_and_swap(&snapshot, new.clone()).is_ok());
+    /// assert!(handle.compare_and_swap(&snapshot, new).is_err());
+    /// ```
+    pub fn compare_and_swap(
+        &self,
+        expected: &IntervalTree<T, P>,
+        new: IntervalTree<T, P>,
+    ) -> Result<IntervalTree<T, P>, IntervalTree<T, P>> {
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        if current.ptr_eq(expected) {
+            Ok(std::mem::replace(&mut *current, new))
+        } else {
+            Err(current.clone())
+        }
+    }
+
+    /// Apply a change to the current version, retrying until it succeeds
+    ///
+    /// `f` may be called more than once if other writers publish new versions
+    /// concurrently, so it should not have side effects. Returns the version
+    /// that was published.
+    pub fn update<F>(&self, mut f: F) -> IntervalTree<T, P>
+    where
+        F: FnMut(&IntervalTree<T, P>) -> IntervalTree<T, P>,
+    {
+        let mut snapshot = self.load();
+        loop {
+            let new = f(&snapshot);
+            match self.compare_and_swap(&snapshot, new.clone()) {
+                Ok(_) => return new,
+                Err(actual) => snapshot = actual,
+            }
+        }
+    }
+
+    /// Publish a new version with the given Interval added
+    pub fn insert(&self, interval: Interval<T, P>) -> IntervalTree<T, P> {
+        self.update(|tree| tree.insert(interval.clone()))
+    }
+
+    /// Publish a new version minus the given Interval, if present
+    pub fn remove(&self, interval: &Interval<T, P>) -> IntervalTree<T, P> {
+        self.update(|tree| tree.remove(interval))
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> Default for LockedIntervalTree<T, P> {
+    fn default() -> Self {
+        Self::from_tree(IntervalTree::new_with_ptr_kind())
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> From<IntervalTree<T, P>> for LockedIntervalTree<T, P> {
+    fn from(tree: IntervalTree<T, P>) -> Self {
+        Self::from_tree(tree)
+    }
+}
@@ -0,0 +1,164 @@
+use std::cmp::{max, min};
+use std::ops::Bound;
+use std::ops::Bound::*;
+
+use crate::shared::{Shared, SharedPointerKind};
+use crate::{Interval, IntervalTree, Node};
+
+impl<T: Ord + Clone, P: SharedPointerKind> Node<T, P> {
+    fn first_interval(&self) -> &Interval<T, P> {
+        match &self.left {
+            None => &self.interval,
+            Some(left_tree) => left_tree.first_interval(),
+        }
+    }
+
+    fn last_interval(&self) -> &Interval<T, P> {
+        match &self.right {
+            None => &self.interval,
+            Some(right_tree) => right_tree.last_interval(),
+        }
+    }
+
+    /// Map every interval while keeping the shape of the tree, mirrored when
+    /// `reversed`, or return `None` as soon as a mapped interval is empty or
+    /// out of order
+    fn map_bounds<U, F>(&self, f: &F, reversed: bool) -> Option<Shared<Node<U, P>, P>>
+    where
+        U: Ord + Clone,
+        F: Fn(&T) -> U,
+    {
+        let (left, right) = if reversed {
+            (&self.right, &self.left)
+        } else {
+            (&self.left, &self.right)
+        };
+        let left = match left {
+            None => None,
+            Some(left_tree) => Some(left_tree.map_bounds(f, reversed)?),
+        };
+        let interval = map_interval(&self.interval, f, reversed);
+        if !Interval::valid(&interval) {
+            return None;
+        }
+        if let Some(left_tree) = &left {
+            if *left_tree.last_interval() >= interval {
+                return None;
+            }
+        }
+        let right = match right {
+            None => None,
+            Some(right_tree) => Some(right_tree.map_bounds(f, reversed)?),
+        };
+        if let Some(right_tree) = &right {
+            if *right_tree.first_interval() <= interval {
+                return None;
+            }
+        }
+        Some(Shared::new(Node::new(interval, left, right)))
+    }
+}
+
+fn map_interval<T, U, P, F>(interval: &Interval<T, P>, f: &F, reversed: bool) -> Interval<U, P>
+where
+    T: Ord + Clone,
+    U: Ord + Clone,
+    P: SharedPointerKind,
+    F: Fn(&T) -> U,
+{
+    if reversed {
+        interval.map_reversed(f)
+    } else {
+        interval.map(f)
+    }
+}
+
+fn bound_value<T>(bound: &Bound<T>) -> Option<&T> {
+    match bound {
+        Included(value) | Excluded(value) => Some(value),
+        Unbounded => None,
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> IntervalTree<T, P> {
+    /// Construct a new IntervalTree by applying a function to the value of
+    /// every bound of every interval
+    ///
+    /// When `f` is strictly increasing the intervals usually stay in the same
+    /// order, so the new tree is built with the same shape in linear time.
+    /// When `f` is decreasing, found by comparing its results for the least
+    /// and greatest bound values in the tree, the bounds of every interval
+    /// are swapped as in `Interval::map_reversed` and the tree is mirrored.
+    /// Otherwise the mapped intervals are sorted and the tree is rebuilt,
+    /// keeping one copy of any intervals that become equal and dropping any
+    /// that become empty.
+    ///
+    /// A tree whose bounds hold fewer than two distinct values can't reveal
+    /// which way `f` goes, so `f` is then treated as increasing.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    ///
+    /// let shifted = tree.map_bounds(|x| i32::from(*x) - 10);
+    /// let mut shifted = shifted.iter().collect::<Vec<Interval<i32>>>();
+    /// shifted.sort();
+    /// assert_eq!(
+    ///     shifted,
+    ///     vec![
+    ///         Interval::new(Included(-9), Excluded(-7)),
+    ///         Interval::new(Included(-5), Unbounded),
+    ///     ]
+    /// );
+    ///
+    /// let negated = tree.map_bounds(|x| -i32::from(*x));
+    /// let mut negated = negated.iter().collect::<Vec<Interval<i32>>>();
+    /// negated.sort();
+    /// assert_eq!(
+    ///     negated,
+    ///     vec![
+    ///         Interval::new(Unbounded, Included(-5)),
+    ///         Interval::new(Excluded(-3), Included(-1)),
+    ///     ]
+    /// );
+    /// ```
+    pub fn map_bounds<U, F>(&self, f: F) -> IntervalTree<U, P>
+    where
+        U: Ord + Clone,
+        F: Fn(&T) -> U,
+    {
+        let values = self
+            .iter_ref()
+            .flat_map(|interval| [interval.low(), interval.high()])
+            .filter_map(bound_value);
+        let range = values.fold(None, |range, value| match range {
+            None => Some((value, value)),
+            Some((least, greatest)) => Some((min(least, value), max(greatest, value))),
+        });
+        let reversed = match range {
+            Some((least, greatest)) if least < greatest => f(least) > f(greatest),
+            _ => false,
+        };
+
+        let root = match &self.root {
+            None => None,
+            Some(node) => match node.map_bounds(&f, reversed) {
+                Some(root) => Some(root),
+                None => {
+                    return self
+                        .iter_ref()
+                        .map(|interval| map_interval(interval, &f, reversed))
+                        .filter(Interval::valid)
+                        .collect()
+                }
+            },
+        };
+        let tree = IntervalTree { root };
+        tree.debug_check_invariants();
+        tree
+    }
+}
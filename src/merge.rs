@@ -0,0 +1,213 @@
+use std::fmt;
+
+use crate::shared::{DefaultSharedPointerKind, SharedPointerKind};
+use crate::{Change, Interval, IntervalTree};
+
+/// An interval that both sides of a merge removed, replacing it differently
+///
+/// The replacements of each side are the intervals it added that overlap
+/// the removed base interval, in sorted order.
+///
+/// # Example
+/// ```
+/// # use std::ops::Bound::*;
+/// # use im_interval_tree::{IntervalTree, Interval, Resolution};
+/// let base : IntervalTree<u8> = IntervalTree::new();
+/// let base = base.insert(Interval::new(Included(1), Excluded(3)));
+/// let ours = base
+///     .remove(&Interval::new(Included(1), Excluded(3)))
+///     .insert(Interval::new(Included(1), Excluded(4)));
+/// let theirs = base
+///     .remove(&Interval::new(Included(1), Excluded(3)))
+///     .insert(Interval::new(Included(2), Excluded(4)));
+///
+/// let mut conflicts = Vec::new();
+/// IntervalTree::merge3(&base, &ours, &theirs, |conflict| {
+///     conflicts.push(conflict.clone());
+///     Resolution::Base
+/// });
+/// assert_eq!(conflicts.len(), 1);
+/// assert_eq!(conflicts[0].ours(), &[Interval::new(Included(1), Excluded(4))]);
+/// assert!(format!("{:?}", conflicts[0]).starts_with("Conflict { base: "));
+/// ```
+pub struct Conflict<T: Ord + Clone, P: SharedPointerKind = DefaultSharedPointerKind> {
+    base: Interval<T, P>,
+    ours: Vec<Interval<T, P>>,
+    theirs: Vec<Interval<T, P>>,
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> Conflict<T, P> {
+    /// Return the interval from the base version that both sides removed
+    pub fn base(&self) -> &Interval<T, P> {
+        &self.base
+    }
+
+    /// Return the intervals our side replaced the base interval with
+    pub fn ours(&self) -> &[Interval<T, P>] {
+        &self.ours
+    }
+
+    /// Return the intervals their side replaced the base interval with
+    pub fn theirs(&self) -> &[Interval<T, P>] {
+        &self.theirs
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> Clone for Conflict<T, P> {
+    fn clone(&self) -> Self {
+        Conflict {
+            base: self.base.clone(),
+            ours: self.ours.clone(),
+            theirs: self.theirs.clone(),
+        }
+    }
+}
+
+impl<T: Ord + Clone + fmt::Debug, P: SharedPointerKind> fmt::Debug for Conflict<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Conflict")
+            .field("base", &self.base)
+            .field("ours", &self.ours)
+            .field("theirs", &self.theirs)
+            .finish()
+    }
+}
+
+/// How to resolve a Conflict in a three-way merge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Resolution {
+    /// Keep only our replacements
+    Ours,
+    /// Keep only their replacements
+    Theirs,
+    /// Keep the base interval and drop the replacements of both sides
+    Base,
+    /// Keep the replacements of both sides
+    Union,
+}
+
+/// Split a list of changes into the added and removed intervals
+#[allow(clippy::type_complexity)]
+fn split_changes<T: Ord + Clone, P: SharedPointerKind>(
+    changes: impl Iterator<Item = Change<Interval<T, P>>>,
+) -> (Vec<Interval<T, P>>, Vec<Interval<T, P>>) {
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    for change in changes {
+        match change {
+            Change::Added(interval) => added.push(interval),
+            Change::Removed(interval) => removed.push(interval),
+        }
+    }
+    (added, removed)
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> IntervalTree<T, P> {
+    /// Merge the changes two versions made to a common base version
+    ///
+    /// The result is `ours` with every interval `theirs` added or removed
+    /// relative to `base` added or removed as well. Both sides are diffed
+    /// against `base`, so regions neither side touched are never visited.
+    ///
+    /// When both sides removed the same base interval but replaced it with
+    /// different overlapping intervals, `resolver` is called to decide which
+    /// replacements to keep. Conflicts are resolved in sorted order of their
+    /// base intervals.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval, Resolution};
+    /// let base : IntervalTree<u8> = IntervalTree::new();
+    /// let base = base.insert(Interval::new(Included(1), Excluded(3)));
+    /// let base = base.insert(Interval::new(Included(5), Excluded(7)));
+    ///
+    /// // We extend the first interval, they move it, and they add another
+    /// let ours = base
+    ///     .remove(&Interval::new(Included(1), Excluded(3)))
+    ///     .insert(Interval::new(Included(1), Excluded(4)));
+    /// let theirs = base
+    ///     .remove(&Interval::new(Included(1), Excluded(3)))
+    ///     .insert(Interval::new(Included(2), Excluded(4)))
+    ///     .insert(Interval::new(Included(8), Excluded(9)));
+    ///
+    /// let merged = IntervalTree::merge3(&base, &ours, &theirs, |conflict| {
+    ///     assert_eq!(conflict.base(), &Interval::new(Included(1), Excluded(3)));
+    ///     Resolution::Ours
+    /// });
+    /// let mut merged = merged.iter().collect::<Vec<Interval<u8>>>();
+    /// merged.sort();
+    /// assert_eq!(
+    ///     merged,
+    ///     vec![
+    ///         Interval::new(Included(1), Excluded(4)),
+    ///         Interval::new(Included(5), Excluded(7)),
+    ///         Interval::new(Included(8), Excluded(9)),
+    ///     ]
+    /// );
+    /// ```
+    pub fn merge3<F>(base: &Self, ours: &Self, theirs: &Self, mut resolver: F) -> IntervalTree<T, P>
+    where
+        F: FnMut(&Conflict<T, P>) -> Resolution,
+    {
+        let (our_added, our_removed) = split_changes(base.diff(ours));
+        let (their_added, their_removed) = split_changes(base.diff(theirs));
+
+        let mut merged = ours.clone();
+        for interval in &their_removed {
+            merged = merged.remove(interval);
+        }
+        for interval in &their_added {
+            merged = merged.insert(interval.clone());
+        }
+
+        let our_added = our_added.into_iter().collect::<IntervalTree<T, P>>();
+        let their_added = their_added.into_iter().collect::<IntervalTree<T, P>>();
+        let replacements = |added: &IntervalTree<T, P>, interval: &Interval<T, P>| {
+            let mut replacements = added.query_interval(interval).collect::<Vec<_>>();
+            replacements.sort();
+            replacements
+        };
+
+        for interval in our_removed {
+            if their_removed.binary_search(&interval).is_err() {
+                continue;
+            }
+            let conflict = Conflict {
+                ours: replacements(&our_added, &interval),
+                theirs: replacements(&their_added, &interval),
+                base: interval,
+            };
+            if conflict.ours == conflict.theirs {
+                continue;
+            }
+            let (discarded, restored): (Vec<_>, _) = match resolver(&conflict) {
+                Resolution::Ours => (
+                    conflict
+                        .theirs
+                        .iter()
+                        .filter(|i| conflict.ours.binary_search(i).is_err())
+                        .collect(),
+                    false,
+                ),
+                Resolution::Theirs => (
+                    conflict
+                        .ours
+                        .iter()
+                        .filter(|i| conflict.theirs.binary_search(i).is_err())
+                        .collect(),
+                    false,
+                ),
+                Resolution::Base => (conflict.ours.iter().chain(&conflict.theirs).collect(), true),
+                Resolution::Union => (Vec::new(), false),
+            };
+            for interval in discarded {
+                merged = merged.remove(interval);
+            }
+            if restored {
+                merged = merged.insert(conflict.base.clone());
+            }
+        }
+        merged
+    }
+}
@@ -0,0 +1,275 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::ops::Bound;
+use std::ops::Bound::*;
+
+use crate::interval::*;
+use crate::shared::SharedPointerKind;
+use crate::{IntervalTree, Node};
+
+/// A type with a distance between any two of its values
+///
+/// This is used to rank intervals by how far they are from a point in
+/// `IntervalTree::k_nearest`.
+pub trait Measure {
+    /// The type of distances between two values
+    type Distance: Ord;
+
+    /// Return the distance between two values
+    fn distance(&self, other: &Self) -> Self::Distance;
+}
+
+macro_rules! impl_measure {
+    ($($t:ty => $d:ty),*) => {
+        $(
+            impl Measure for $t {
+                type Distance = $d;
+
+                fn distance(&self, other: &Self) -> $d {
+                    self.abs_diff(*other)
+                }
+            }
+        )*
+    };
+}
+
+impl_measure!(
+    u8 => u8, u16 => u16, u32 => u32, u64 => u64, u128 => u128, usize => usize,
+    i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize
+);
+
+/// The distance from a point to the closest value between two bounds
+fn distance_to_bounds<T: Ord + Measure>(low: &Bound<T>, high: &Bound<T>, point: &T) -> T::Distance {
+    match low {
+        Included(low) if point < low => return point.distance(low),
+        Excluded(low) if point <= low => return point.distance(low),
+        _ => {}
+    }
+    match high {
+        Included(high) if point > high => point.distance(high),
+        Excluded(high) if point >= high => point.distance(high),
+        _ => point.distance(point),
+    }
+}
+
+/// Whether an upper bound is at or before the given point
+fn ends_before<T: Ord>(high: &Bound<T>, point: &T) -> bool {
+    match high {
+        Included(high) | Excluded(high) => high <= point,
+        Unbounded => false,
+    }
+}
+
+/// Whether every interval with the given lower bound must end after the point
+fn starts_beyond<T: Ord>(low: &Bound<T>, point: &T) -> bool {
+    match low {
+        Included(low) => low > point,
+        Excluded(low) => low >= point,
+        Unbounded => false,
+    }
+}
+
+/// Whether a lower bound is at or after the given point
+fn starts_after<T: Ord>(low: &Bound<T>, point: &T) -> bool {
+    match low {
+        Included(low) | Excluded(low) => low >= point,
+        Unbounded => false,
+    }
+}
+
+enum Candidate<'a, T: Ord + Clone + Measure, P: SharedPointerKind> {
+    Interval(T::Distance, &'a Interval<T, P>),
+    Subtree(T::Distance, &'a Node<T, P>),
+}
+
+impl<'a, T: Ord + Clone + Measure, P: SharedPointerKind> Candidate<'a, T, P> {
+    fn subtree(node: &'a Node<T, P>, point: &T) -> Self {
+        Candidate::Subtree(distance_to_bounds(&node.min, &node.max, point), node)
+    }
+
+    fn interval(interval: &'a Interval<T, P>, point: &T) -> Self {
+        Candidate::Interval(
+            distance_to_bounds(interval.low(), interval.high(), point),
+            interval,
+        )
+    }
+
+    fn distance(&self) -> &T::Distance {
+        match self {
+            Candidate::Interval(distance, _) | Candidate::Subtree(distance, _) => distance,
+        }
+    }
+}
+
+impl<'a, T: Ord + Clone + Measure, P: SharedPointerKind> PartialEq for Candidate<'a, T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<'a, T: Ord + Clone + Measure, P: SharedPointerKind> Eq for Candidate<'a, T, P> {}
+
+impl<'a, T: Ord + Clone + Measure, P: SharedPointerKind> PartialOrd for Candidate<'a, T, P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Candidates are ordered so that the closest is the greatest, with intervals
+/// ahead of subtrees at the same distance so that ties need no further search
+impl<'a, T: Ord + Clone + Measure, P: SharedPointerKind> Ord for Candidate<'a, T, P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let by_distance = other.distance().cmp(self.distance());
+        let by_kind = match (self, other) {
+            (Candidate::Interval(_, a), Candidate::Interval(_, b)) => b.cmp(a),
+            (Candidate::Interval(..), Candidate::Subtree(..)) => Ordering::Greater,
+            (Candidate::Subtree(..), Candidate::Interval(..)) => Ordering::Less,
+            (Candidate::Subtree(..), Candidate::Subtree(..)) => Ordering::Equal,
+        };
+        by_distance.then(by_kind)
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> Node<T, P> {
+    fn nearest_before<'a>(&'a self, point: &T, best: &mut Option<&'a Interval<T, P>>) {
+        if starts_beyond(&self.min, point) {
+            return;
+        }
+        if let Some(best) = best {
+            if high_bound_cmp(&self.max, best.high()) != Ordering::Greater {
+                return;
+            }
+        }
+        if let Some(right_tree) = &self.right {
+            right_tree.nearest_before(point, best);
+        }
+        if ends_before(self.interval.high(), point) {
+            let is_better = match best {
+                None => true,
+                Some(best) => {
+                    high_bound_cmp(self.interval.high(), best.high()) == Ordering::Greater
+                }
+            };
+            if is_better {
+                *best = Some(&self.interval);
+            }
+        }
+        if let Some(left_tree) = &self.left {
+            left_tree.nearest_before(point, best);
+        }
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> IntervalTree<T, P> {
+    /// Return the interval that ends closest to, but not after, the given point
+    ///
+    /// This is the interval with the greatest upper bound that is less than or
+    /// equal to `point`. If several intervals share that upper bound, the one
+    /// that sorts last is returned.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Included(2), Included(4)));
+    /// let tree = tree.insert(Interval::new(Included(8), Excluded(9)));
+    ///
+    /// assert_eq!(
+    ///     tree.nearest_before(&6),
+    ///     Some(Interval::new(Included(2), Included(4)))
+    /// );
+    /// assert_eq!(tree.nearest_before(&0), None);
+    /// ```
+    pub fn nearest_before(&self, point: &T) -> Option<Interval<T, P>> {
+        let mut best = None;
+        if let Some(node) = &self.root {
+            node.nearest_before(point, &mut best);
+        }
+        best.cloned()
+    }
+
+    /// Return the interval that starts closest to, but not before, the given point
+    ///
+    /// This is the interval with the least lower bound that is greater than or
+    /// equal to `point`. If several intervals share that lower bound, the one
+    /// that sorts first is returned.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Included(5), Unbounded));
+    /// let tree = tree.insert(Interval::new(Excluded(5), Included(6)));
+    ///
+    /// assert_eq!(
+    ///     tree.nearest_after(&4),
+    ///     Some(Interval::new(Included(5), Unbounded))
+    /// );
+    /// assert_eq!(tree.nearest_after(&6), None);
+    /// ```
+    pub fn nearest_after(&self, point: &T) -> Option<Interval<T, P>> {
+        let mut best = None;
+        let mut current = &self.root;
+        while let Some(node) = current {
+            if starts_after(node.interval.low(), point) {
+                best = Some(&node.interval);
+                current = &node.left;
+            } else {
+                current = &node.right;
+            }
+        }
+        best.cloned()
+    }
+
+    /// Return the `k` intervals closest to the given point, nearest first
+    ///
+    /// The distance from an interval to a point is zero if the interval
+    /// contains the point, and otherwise the distance to its nearest bound.
+    /// When several intervals are at the same distance, which of them are
+    /// returned is unspecified.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// let tree = tree.insert(Interval::new(Included(4), Included(5)));
+    /// let tree = tree.insert(Interval::new(Included(9), Unbounded));
+    ///
+    /// assert_eq!(
+    ///     tree.k_nearest(&7, 2),
+    ///     vec![
+    ///         Interval::new(Included(4), Included(5)),
+    ///         Interval::new(Included(9), Unbounded),
+    ///     ]
+    /// );
+    /// ```
+    pub fn k_nearest(&self, point: &T, k: usize) -> Vec<Interval<T, P>>
+    where
+        T: Measure,
+    {
+        let mut nearest = Vec::new();
+        let mut candidates = BinaryHeap::new();
+        if let Some(node) = &self.root {
+            candidates.push(Candidate::subtree(node, point));
+        }
+        while nearest.len() < k {
+            match candidates.pop() {
+                None => break,
+                Some(Candidate::Interval(_, interval)) => nearest.push(interval.clone()),
+                Some(Candidate::Subtree(_, node)) => {
+                    candidates.push(Candidate::interval(&node.interval, point));
+                    for child in node.left.iter().chain(&node.right) {
+                        candidates.push(Candidate::subtree(child, point));
+                    }
+                }
+            }
+        }
+        nearest
+    }
+}
@@ -0,0 +1,209 @@
+use crate::shared::SharedPointerKind;
+use crate::{Change, DefaultSharedPointerKind, Interval, IntervalTree};
+
+/// Identifies a subscription to an ObservableIntervalTree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(usize);
+
+type Callback<T, P> = Box<dyn FnMut(&Change<Interval<T, P>>)>;
+
+struct Subscriber<T: Ord + Clone, P: SharedPointerKind> {
+    id: SubscriptionId,
+    window: Option<Interval<T, P>>,
+    callback: Callback<T, P>,
+}
+
+fn notify<T: Ord + Clone, P: SharedPointerKind>(
+    subscribers: &mut [Subscriber<T, P>],
+    change: &Change<Interval<T, P>>,
+) {
+    let interval = match change {
+        Change::Added(interval) | Change::Removed(interval) => interval,
+    };
+    for subscriber in subscribers {
+        let watched = match &subscriber.window {
+            None => true,
+            Some(window) => window.overlaps(interval),
+        };
+        if watched {
+            (subscriber.callback)(change);
+        }
+    }
+}
+
+/// A mutable handle to an IntervalTree that notifies subscribers of changes
+///
+/// Every interval added or removed through the handle is reported to each
+/// subscriber as a `Change`, in sorted order for bulk updates. Subscribers
+/// registered with a window only hear about intervals that overlap it.
+///
+/// # Example
+/// ```
+/// # use std::cell::RefCell;
+/// # use std::ops::Bound::*;
+/// # use std::rc::Rc;
+/// # use im_interval_tree::{Change, Interval, ObservableIntervalTree};
+/// let mut tree : ObservableIntervalTree<u8> = ObservableIntervalTree::new();
+/// let seen = Rc::new(RefCell::new(Vec::new()));
+///
+/// let log = seen.clone();
+/// tree.subscribe_window(Interval::new(Included(0), Excluded(10)), move |change| {
+///     log.borrow_mut().push(change.clone())
+/// });
+///
+/// tree.insert(Interval::new(Included(1), Excluded(3)));
+/// tree.insert(Interval::new(Included(20), Excluded(30)));
+/// tree.remove(&Interval::new(Included(1), Excluded(3)));
+///
+/// assert_eq!(
+///     *seen.borrow(),
+///     vec![
+///         Change::Added(Interval::new(Included(1), Excluded(3))),
+///         Change::Removed(Interval::new(Included(1), Excluded(3))),
+///     ]
+/// );
+/// ```
+pub struct ObservableIntervalTree<T: Ord + Clone, P: SharedPointerKind = DefaultSharedPointerKind> {
+    tree: IntervalTree<T, P>,
+    subscribers: Vec<Subscriber<T, P>>,
+    next_id: usize,
+}
+
+impl<T: Ord + Clone> ObservableIntervalTree<T> {
+    /// Construct a handle to an empty IntervalTree with no subscribers
+    pub fn new() -> ObservableIntervalTree<T> {
+        Self::from_tree(IntervalTree::new())
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> ObservableIntervalTree<T, P> {
+    /// Construct a handle to the given tree with no subscribers
+    pub fn from_tree(tree: IntervalTree<T, P>) -> ObservableIntervalTree<T, P> {
+        ObservableIntervalTree {
+            tree,
+            subscribers: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Return the current version of the tree
+    pub fn tree(&self) -> &IntervalTree<T, P> {
+        &self.tree
+    }
+
+    /// Call `callback` with every future change
+    pub fn subscribe<F>(&mut self, callback: F) -> SubscriptionId
+    where
+        F: FnMut(&Change<Interval<T, P>>) + 'static,
+    {
+        self.add_subscriber(None, Box::new(callback))
+    }
+
+    /// Call `callback` with every future change to an interval that overlaps
+    /// with the given window
+    pub fn subscribe_window<F>(&mut self, window: Interval<T, P>, callback: F) -> SubscriptionId
+    where
+        F: FnMut(&Change<Interval<T, P>>) + 'static,
+    {
+        self.add_subscriber(Some(window), Box::new(callback))
+    }
+
+    /// Stop calling a subscriber, returning whether it was subscribed
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let before = self.subscribers.len();
+        self.subscribers.retain(|subscriber| subscriber.id != id);
+        self.subscribers.len() != before
+    }
+
+    /// Add the given Interval, returning whether it was newly added
+    pub fn insert(&mut self, interval: Interval<T, P>) -> bool {
+        let (tree, added) = self.tree.insert_full(interval.clone());
+        if added {
+            self.tree = tree;
+            notify(&mut self.subscribers, &Change::Added(interval));
+        }
+        added
+    }
+
+    /// Remove the given Interval, returning it if it was present
+    pub fn remove(&mut self, interval: &Interval<T, P>) -> Option<Interval<T, P>> {
+        let (tree, removed) = self.tree.remove_full(interval);
+        if let Some(removed) = &removed {
+            self.tree = tree;
+            notify(&mut self.subscribers, &Change::Removed(removed.clone()));
+        }
+        removed
+    }
+
+    /// Replace the tree with the result of applying a change to it,
+    /// notifying subscribers of every interval added or removed
+    ///
+    /// The changes are found by diffing the old and new versions, so this
+    /// suits bulk operations like `remove_overlapping` or `union`.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::cell::Cell;
+    /// # use std::ops::Bound::*;
+    /// # use std::rc::Rc;
+    /// # use im_interval_tree::{Interval, ObservableIntervalTree};
+    /// let mut tree : ObservableIntervalTree<u8> = ObservableIntervalTree::new();
+    /// tree.insert(Interval::new(Included(1), Excluded(3)));
+    /// tree.insert(Interval::new(Included(2), Excluded(4)));
+    ///
+    /// let count = Rc::new(Cell::new(0));
+    /// let counter = count.clone();
+    /// tree.subscribe(move |_| counter.set(counter.get() + 1));
+    ///
+    /// tree.update(|tree| tree.remove_overlapping(&Interval::new(Included(0), Included(5))).0);
+    /// assert_eq!(count.get(), 2);
+    /// ```
+    pub fn update<F>(&mut self, f: F)
+    where
+        F: FnOnce(&IntervalTree<T, P>) -> IntervalTree<T, P>,
+    {
+        let tree = f(&self.tree);
+        self.replace(tree);
+    }
+
+    /// Replace the tree with another version, notifying subscribers of every
+    /// interval added or removed
+    pub fn replace(&mut self, tree: IntervalTree<T, P>) {
+        let old = std::mem::replace(&mut self.tree, tree);
+        if self.subscribers.is_empty() {
+            return;
+        }
+        for change in old.diff(&self.tree) {
+            notify(&mut self.subscribers, &change);
+        }
+    }
+
+    fn add_subscriber(
+        &mut self,
+        window: Option<Interval<T, P>>,
+        callback: Callback<T, P>,
+    ) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        self.subscribers.push(Subscriber {
+            id,
+            window,
+            callback,
+        });
+        id
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> Default for ObservableIntervalTree<T, P> {
+    fn default() -> Self {
+        Self::from_tree(IntervalTree::new_with_ptr_kind())
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> From<IntervalTree<T, P>>
+    for ObservableIntervalTree<T, P>
+{
+    fn from(tree: IntervalTree<T, P>) -> Self {
+        Self::from_tree(tree)
+    }
+}
@@ -0,0 +1,266 @@
+use std::ops::Bound::*;
+
+use rayon::iter::{FromParallelIterator, IntoParallelIterator, ParallelIterator};
+use rayon::slice::ParallelSliceMut;
+
+use crate::shared::{Shared, SharedPointerKind};
+use crate::{Interval, IntervalTree, Node};
+
+/// Subtrees shorter than this are processed sequentially
+const SEQUENTIAL_HEIGHT: usize = 8;
+
+/// Slices shorter than this are built into trees sequentially
+const SEQUENTIAL_LEN: usize = 1 << SEQUENTIAL_HEIGHT;
+
+// Work is passed between threads as IntervalTrees rather than bare nodes, so
+// that the parallel APIs only need the public tree and interval types to be
+// `Send + Sync`, which holds for any pointer kind backed by `Arc`.
+
+enum Task<T: Ord + Clone, P: SharedPointerKind> {
+    /// Every interval in this subtree
+    Subtree(IntervalTree<T, P>),
+    /// Only the interval stored at the root of this subtree
+    Single(IntervalTree<T, P>),
+}
+
+/// A portion of a query that can be split in two and run in parallel
+struct QueryPiece<T: Ord + Clone, P: SharedPointerKind> {
+    tasks: Vec<Task<T, P>>,
+    query: Interval<T, P>,
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> QueryPiece<T, P> {
+    /// Push the children of a node whose subtrees may overlap the query
+    fn push_children(tasks: &mut Vec<Task<T, P>>, node: &Node<T, P>, query: &Interval<T, P>) {
+        if let Some(left_tree) = &node.left {
+            if left_tree.max_reaches(query.low()) {
+                tasks.push(Task::Subtree(IntervalTree {
+                    root: Some(left_tree.clone()),
+                }));
+            }
+        }
+        if let Some(right_tree) = &node.right {
+            if right_tree.min_reaches(query.high()) {
+                tasks.push(Task::Subtree(IntervalTree {
+                    root: Some(right_tree.clone()),
+                }));
+            }
+        }
+    }
+
+    fn split(mut self) -> (Self, Option<Self>) {
+        let other = if self.tasks.len() > 1 {
+            self.tasks.split_off(self.tasks.len() / 2)
+        } else if let Some(Task::Subtree(tree)) = self.tasks.last() {
+            let tree = tree.clone();
+            let mut other = Vec::new();
+            if let Some(node) = &tree.root {
+                Self::push_children(&mut other, node, &self.query);
+            }
+            self.tasks[0] = Task::Single(tree);
+            other
+        } else {
+            return (self, None);
+        };
+
+        if other.is_empty() {
+            (self, None)
+        } else {
+            let query = self.query.clone();
+            (
+                self,
+                Some(QueryPiece {
+                    tasks: other,
+                    query,
+                }),
+            )
+        }
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> Iterator for QueryPiece<T, P> {
+    type Item = Interval<T, P>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(task) = self.tasks.pop() {
+            let tree = match task {
+                Task::Single(tree) => tree,
+                Task::Subtree(tree) => {
+                    if let Some(node) = &tree.root {
+                        Self::push_children(&mut self.tasks, node, &self.query);
+                    }
+                    tree
+                }
+            };
+            if let Some(node) = &tree.root {
+                if self.query.overlaps(&node.interval) {
+                    return Some(node.interval.clone());
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<T, P> IntervalTree<T, P>
+where
+    T: Ord + Clone,
+    P: SharedPointerKind,
+    Interval<T, P>: Send + Sync,
+    IntervalTree<T, P>: Send + Sync,
+{
+    fn par_from_sorted(intervals: &[Interval<T, P>]) -> IntervalTree<T, P> {
+        if intervals.len() < SEQUENTIAL_LEN {
+            return IntervalTree {
+                root: Node::from_sorted(intervals),
+            };
+        }
+        let mid = intervals.len() / 2;
+        let (left, right) = rayon::join(
+            || Self::par_from_sorted(&intervals[..mid]),
+            || Self::par_from_sorted(&intervals[mid + 1..]),
+        );
+        IntervalTree {
+            root: Some(Shared::new(Node::new(
+                intervals[mid].clone(),
+                left.root,
+                right.root,
+            ))),
+        }
+    }
+
+    fn par_union_trees(a: &IntervalTree<T, P>, b: &IntervalTree<T, P>) -> IntervalTree<T, P> {
+        let root = match (&a.root, &b.root) {
+            (Some(a_node), Some(b_node))
+                if a_node.height > SEQUENTIAL_HEIGHT || b_node.height > SEQUENTIAL_HEIGHT =>
+            {
+                let (less, _, greater) = Node::split(&b.root, &a_node.interval);
+                let (a_left, a_right) = (
+                    IntervalTree {
+                        root: a_node.left.clone(),
+                    },
+                    IntervalTree {
+                        root: a_node.right.clone(),
+                    },
+                );
+                let (less, greater) = (IntervalTree { root: less }, IntervalTree { root: greater });
+                let (left, right) = rayon::join(
+                    || Self::par_union_trees(&a_left, &less),
+                    || Self::par_union_trees(&a_right, &greater),
+                );
+                let joined = Node::join(left.root, a_node.interval.clone(), right.root);
+                Some(Shared::new(joined))
+            }
+            _ => Node::union(&a.root, &b.root),
+        };
+        IntervalTree { root }
+    }
+
+    /// Return a parallel iterator over all the intervals in the tree that
+    /// overlap with the given interval
+    ///
+    /// Work is split across subtrees, so results are produced in no
+    /// particular order.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTreeSync, IntervalSync};
+    /// use rayon::prelude::*;
+    ///
+    /// let tree : IntervalTreeSync<u32> = (0..1000)
+    ///     .map(|i| IntervalSync::new_sync(Included(i), Excluded(i + 10)))
+    ///     .collect();
+    ///
+    /// let query = IntervalSync::new_sync(Included(100), Excluded(200));
+    /// assert_eq!(tree.par_query_interval(&query).count(), 109);
+    /// ```
+    pub fn par_query_interval(
+        &self,
+        interval: &Interval<T, P>,
+    ) -> impl ParallelIterator<Item = Interval<T, P>> + '_ {
+        let piece = QueryPiece {
+            tasks: vec![Task::Subtree(self.clone())],
+            query: interval.clone(),
+        };
+        rayon::iter::split(piece, QueryPiece::split).flat_map_iter(|piece| piece)
+    }
+
+    /// Return a parallel iterator over all the intervals in the tree
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTreeSync, IntervalSync};
+    /// use rayon::prelude::*;
+    ///
+    /// let tree : IntervalTreeSync<u32> = (0..1000)
+    ///     .map(|i| IntervalSync::new_sync(Included(i), Excluded(i + 10)))
+    ///     .collect();
+    ///
+    /// assert_eq!(tree.par_iter().count(), 1000);
+    /// ```
+    pub fn par_iter(&self) -> impl ParallelIterator<Item = Interval<T, P>> + '_ {
+        self.par_query_interval(&Interval::new_with_ptr_kind(Unbounded, Unbounded))
+    }
+
+    /// Construct a new IntervalTree containing the intervals of both trees,
+    /// merging subtrees in parallel
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTreeSync, IntervalSync};
+    /// let evens : IntervalTreeSync<u32> = (0..1000)
+    ///     .map(|i| IntervalSync::new_sync(Included(i * 2), Included(i * 2)))
+    ///     .collect();
+    /// let odds : IntervalTreeSync<u32> = (0..1000)
+    ///     .map(|i| IntervalSync::new_sync(Included(i * 2 + 1), Included(i * 2 + 1)))
+    ///     .collect();
+    ///
+    /// assert_eq!(evens.par_union(&odds).iter().count(), 2000);
+    /// ```
+    pub fn par_union(&self, other: &Self) -> IntervalTree<T, P> {
+        let tree = Self::par_union_trees(self, other);
+        tree.debug_check_invariants();
+        tree
+    }
+}
+
+/// Builds a balanced IntervalTree, sorting and building subtrees in parallel
+///
+/// # Example
+/// ```
+/// # use std::ops::Bound::*;
+/// # use im_interval_tree::{IntervalTreeSync, IntervalSync};
+/// use rayon::prelude::*;
+///
+/// let tree : IntervalTreeSync<u32> = (0..1000u32)
+///     .into_par_iter()
+///     .map(|i| IntervalSync::new_sync(Included(i), Excluded(i + 10)))
+///     .collect();
+///
+/// assert_eq!(tree.iter().count(), 1000);
+/// ```
+impl<T, P> FromParallelIterator<Interval<T, P>> for IntervalTree<T, P>
+where
+    T: Ord + Clone,
+    P: SharedPointerKind,
+    Interval<T, P>: Send + Sync,
+    IntervalTree<T, P>: Send + Sync,
+{
+    fn from_par_iter<I>(par_iter: I) -> Self
+    where
+        I: IntoParallelIterator<Item = Interval<T, P>>,
+    {
+        let mut intervals = par_iter
+            .into_par_iter()
+            .filter(Interval::valid)
+            .collect::<Vec<_>>();
+        intervals.par_sort_unstable();
+        intervals.dedup();
+        let tree = Self::par_from_sorted(&intervals);
+        tree.debug_check_invariants();
+        tree
+    }
+}
@@ -0,0 +1,196 @@
+use std::ops::Bound::*;
+use std::ops::{Bound, RangeBounds};
+
+use crate::shared::SharedPointerKind;
+use crate::{Interval, IntervalTree, Node};
+
+/// The position of one end of an interval, where unbounded ends are infinite
+enum Endpoint<'a, T> {
+    NegativeInfinity,
+    Value(&'a T),
+    PositiveInfinity,
+}
+
+impl<'a, T: Ord> Endpoint<'a, T> {
+    fn low(bound: &'a Bound<T>) -> Self {
+        match bound {
+            Included(low) | Excluded(low) => Endpoint::Value(low),
+            Unbounded => Endpoint::NegativeInfinity,
+        }
+    }
+
+    fn high(bound: &'a Bound<T>) -> Self {
+        match bound {
+            Included(high) | Excluded(high) => Endpoint::Value(high),
+            Unbounded => Endpoint::PositiveInfinity,
+        }
+    }
+
+    fn is_before(&self, start: &Bound<T>) -> bool {
+        match (self, start) {
+            (_, Unbounded) => false,
+            (Endpoint::NegativeInfinity, _) => true,
+            (Endpoint::PositiveInfinity, _) => false,
+            (Endpoint::Value(value), Included(start)) => *value < start,
+            (Endpoint::Value(value), Excluded(start)) => *value <= start,
+        }
+    }
+
+    fn is_after(&self, end: &Bound<T>) -> bool {
+        match (self, end) {
+            (_, Unbounded) => false,
+            (Endpoint::NegativeInfinity, _) => false,
+            (Endpoint::PositiveInfinity, _) => true,
+            (Endpoint::Value(value), Included(end)) => *value > end,
+            (Endpoint::Value(value), Excluded(end)) => *value >= end,
+        }
+    }
+}
+
+/// An Iterator over the intervals whose lower bound lies in a range, in sorted order
+struct RangeByLow<'a, T: Ord + Clone, P: SharedPointerKind> {
+    stack: Vec<&'a Node<T, P>>,
+    start: Bound<T>,
+    end: Bound<T>,
+}
+
+impl<'a, T: Ord + Clone, P: SharedPointerKind> RangeByLow<'a, T, P> {
+    fn push_left(&mut self, mut current: Option<&'a Node<T, P>>) {
+        while let Some(node) = current {
+            if Endpoint::low(node.interval.low()).is_before(&self.start) {
+                current = node.right.as_deref();
+            } else {
+                self.stack.push(node);
+                current = node.left.as_deref();
+            }
+        }
+    }
+}
+
+impl<'a, T: Ord + Clone, P: SharedPointerKind> Iterator for RangeByLow<'a, T, P> {
+    type Item = Interval<T, P>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        if Endpoint::low(node.interval.low()).is_after(&self.end) {
+            self.stack.clear();
+            return None;
+        }
+        self.push_left(node.right.as_deref());
+        Some(node.interval.clone())
+    }
+}
+
+/// An Iterator over the intervals whose upper bound lies in a range
+struct RangeByHigh<'a, T: Ord + Clone, P: SharedPointerKind> {
+    stack: Vec<&'a Node<T, P>>,
+    start: Bound<T>,
+    end: Bound<T>,
+}
+
+impl<'a, T: Ord + Clone, P: SharedPointerKind> RangeByHigh<'a, T, P> {
+    fn may_contain(&self, node: &Node<T, P>) -> bool {
+        !Endpoint::high(&node.max).is_before(&self.start)
+            && !Endpoint::low(&node.min).is_after(&self.end)
+    }
+}
+
+impl<'a, T: Ord + Clone, P: SharedPointerKind> Iterator for RangeByHigh<'a, T, P> {
+    type Item = Interval<T, P>;
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.stack.pop() {
+            for child in node.left.iter().chain(&node.right) {
+                if self.may_contain(child) {
+                    self.stack.push(child);
+                }
+            }
+            let high = Endpoint::high(node.interval.high());
+            if !high.is_before(&self.start) && !high.is_after(&self.end) {
+                return Some(node.interval.clone());
+            }
+        }
+        None
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> IntervalTree<T, P> {
+    /// Return an Iterator over all the intervals in the tree whose lower bound
+    /// lies in the given range, in sorted order
+    ///
+    /// Whether a lower bound is included or excluded does not matter, only its
+    /// value. An unbounded lower bound only lies in ranges with no start.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(9)));
+    /// let tree = tree.insert(Interval::new(Excluded(3), Included(4)));
+    /// let tree = tree.insert(Interval::new(Included(7), Unbounded));
+    ///
+    /// assert_eq!(
+    ///     tree.range_by_low(2..=7).collect::<Vec<Interval<u8>>>(),
+    ///     vec![
+    ///         Interval::new(Excluded(3), Included(4)),
+    ///         Interval::new(Included(7), Unbounded),
+    ///     ]
+    /// );
+    /// ```
+    pub fn range_by_low<R: RangeBounds<T>>(
+        &self,
+        range: R,
+    ) -> impl Iterator<Item = Interval<T, P>> + '_ {
+        let mut iter = RangeByLow {
+            stack: Vec::new(),
+            start: range.start_bound().cloned(),
+            end: range.end_bound().cloned(),
+        };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+
+    /// Return an Iterator over all the intervals in the tree whose upper bound
+    /// lies in the given range
+    ///
+    /// Subtrees are skipped using their cached bounds, so intervals are
+    /// produced in no particular order.
+    ///
+    /// Whether an upper bound is included or excluded does not matter, only
+    /// its value. An unbounded upper bound only lies in ranges with no end.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = IntervalTree::new();
+    /// let tree = tree.insert(Interval::new(Included(1), Excluded(9)));
+    /// let tree = tree.insert(Interval::new(Excluded(3), Included(4)));
+    /// let tree = tree.insert(Interval::new(Included(7), Unbounded));
+    ///
+    /// let mut ending = tree.range_by_high(5..).collect::<Vec<Interval<u8>>>();
+    /// ending.sort();
+    /// assert_eq!(
+    ///     ending,
+    ///     vec![
+    ///         Interval::new(Included(1), Excluded(9)),
+    ///         Interval::new(Included(7), Unbounded),
+    ///     ]
+    /// );
+    /// ```
+    pub fn range_by_high<R: RangeBounds<T>>(
+        &self,
+        range: R,
+    ) -> impl Iterator<Item = Interval<T, P>> + '_ {
+        let mut iter = RangeByHigh {
+            stack: Vec::new(),
+            start: range.start_bound().cloned(),
+            end: range.end_bound().cloned(),
+        };
+        if let Some(node) = &self.root {
+            if iter.may_contain(node) {
+                iter.stack.push(node);
+            }
+        }
+        iter
+    }
+}
@@ -1,5 +1,242 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// A kind of reference-counted pointer used to share structure between trees
+///
+/// IntervalTree and Interval are generic over the kind of pointer they use, so
+/// that single-threaded code can use cheap `Rc` pointers while trees that are
+/// sent between threads use `Arc`. Both kinds can be used in the same program.
+///
+/// The kind also decides how the bounds of intervals are stored. `RcK` and
+/// `ArcK` share each bound behind its own pointer, so cloning an interval
+/// never clones a `T`. `InlineRcK` and `InlineArcK` store bounds inline,
+/// which saves an allocation per bound when `T` is `Copy` or cheap to clone.
+pub trait SharedPointerKind: 'static {
+    /// The pointer type for a value of type `T`
+    type Pointer<T>: Clone + Deref<Target = T>;
+
+    /// The storage type for the bounds of intervals
+    type Storage<T: Clone>: Clone + Deref<Target = T>;
+
+    /// Whether `Storage` holds its value inline rather than in an allocation
+    const STORES_INLINE: bool;
+
+    /// Allocate a new shared value
+    fn new<T>(value: T) -> Self::Pointer<T>;
+
+    /// Check whether two pointers point to the same allocation
+    fn ptr_eq<T>(a: &Self::Pointer<T>, b: &Self::Pointer<T>) -> bool;
+
+    /// Store a bound
+    fn store<T: Clone>(value: T) -> Self::Storage<T>;
+}
+
+/// Shared pointers backed by `Rc`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RcK;
+
+impl SharedPointerKind for RcK {
+    type Pointer<T> = Rc<T>;
+    type Storage<T: Clone> = Rc<T>;
+    const STORES_INLINE: bool = false;
+
+    fn new<T>(value: T) -> Rc<T> {
+        Rc::new(value)
+    }
+
+    fn ptr_eq<T>(a: &Rc<T>, b: &Rc<T>) -> bool {
+        Rc::ptr_eq(a, b)
+    }
+
+    fn store<T: Clone>(value: T) -> Rc<T> {
+        Rc::new(value)
+    }
+}
+
+/// Shared pointers backed by `Arc`, allowing trees to be sent between threads
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArcK;
+
+impl SharedPointerKind for ArcK {
+    type Pointer<T> = Arc<T>;
+    type Storage<T: Clone> = Arc<T>;
+    const STORES_INLINE: bool = false;
+
+    fn new<T>(value: T) -> Arc<T> {
+        Arc::new(value)
+    }
+
+    fn ptr_eq<T>(a: &Arc<T>, b: &Arc<T>) -> bool {
+        Arc::ptr_eq(a, b)
+    }
+
+    fn store<T: Clone>(value: T) -> Arc<T> {
+        Arc::new(value)
+    }
+}
+
+/// A value stored inline, used by the inline pointer kinds to hold bounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Inline<T>(T);
+
+impl<T> Deref for Inline<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+/// Shared pointers backed by `Rc`, with bounds stored inline
+///
+/// An `Interval` of this kind needs no allocations of its own, so each node
+/// of a tree is a single allocation. Cloning an interval clones its bounds,
+/// so this suits `Copy` types like integers rather than types like `String`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InlineRcK;
+
+impl SharedPointerKind for InlineRcK {
+    type Pointer<T> = Rc<T>;
+    type Storage<T: Clone> = Inline<T>;
+    const STORES_INLINE: bool = true;
+
+    fn new<T>(value: T) -> Rc<T> {
+        Rc::new(value)
+    }
+
+    fn ptr_eq<T>(a: &Rc<T>, b: &Rc<T>) -> bool {
+        Rc::ptr_eq(a, b)
+    }
+
+    fn store<T: Clone>(value: T) -> Inline<T> {
+        Inline(value)
+    }
+}
+
+/// Shared pointers backed by `Arc`, with bounds stored inline
+///
+/// This is the thread-safe counterpart of `InlineRcK`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InlineArcK;
+
+impl SharedPointerKind for InlineArcK {
+    type Pointer<T> = Arc<T>;
+    type Storage<T: Clone> = Inline<T>;
+    const STORES_INLINE: bool = true;
+
+    fn new<T>(value: T) -> Arc<T> {
+        Arc::new(value)
+    }
+
+    fn ptr_eq<T>(a: &Arc<T>, b: &Arc<T>) -> bool {
+        Arc::ptr_eq(a, b)
+    }
+
+    fn store<T: Clone>(value: T) -> Inline<T> {
+        Inline(value)
+    }
+}
+
+/// The pointer kind used when none is specified
+///
+/// This is `RcK`, or `ArcK` when the `arc` feature is enabled.
 #[cfg(not(feature = "arc"))]
-pub type Shared<T> = std::rc::Rc<T>;
+pub type DefaultSharedPointerKind = RcK;
 
+/// The pointer kind used when none is specified
+///
+/// This is `RcK`, or `ArcK` when the `arc` feature is enabled.
 #[cfg(feature = "arc")]
-pub type Shared<T> = std::sync::Arc<T>;
+pub type DefaultSharedPointerKind = ArcK;
+
+pub struct Shared<T, P: SharedPointerKind>(P::Pointer<T>);
+
+impl<T, P: SharedPointerKind> Shared<T, P> {
+    pub fn new(value: T) -> Self {
+        Shared(P::new(value))
+    }
+
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        P::ptr_eq(&a.0, &b.0)
+    }
+}
+
+impl<T, P: SharedPointerKind> Clone for Shared<T, P> {
+    fn clone(&self) -> Self {
+        Shared(self.0.clone())
+    }
+}
+
+impl<T, P: SharedPointerKind> Deref for Shared<T, P> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: PartialEq, P: SharedPointerKind> PartialEq for Shared<T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq, P: SharedPointerKind> Eq for Shared<T, P> {}
+
+impl<T: Hash, P: SharedPointerKind> Hash for Shared<T, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl<T: fmt::Debug, P: SharedPointerKind> fmt::Debug for Shared<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+/// A value stored the way the pointer kind stores bounds
+pub struct Stored<T: Clone, P: SharedPointerKind>(P::Storage<T>);
+
+impl<T: Clone, P: SharedPointerKind> Stored<T, P> {
+    pub fn new(value: T) -> Self {
+        Stored(P::store(value))
+    }
+}
+
+impl<T: Clone, P: SharedPointerKind> Clone for Stored<T, P> {
+    fn clone(&self) -> Self {
+        Stored(self.0.clone())
+    }
+}
+
+impl<T: Clone, P: SharedPointerKind> Deref for Stored<T, P> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Clone + PartialEq, P: SharedPointerKind> PartialEq for Stored<T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Clone + Eq, P: SharedPointerKind> Eq for Stored<T, P> {}
+
+impl<T: Clone + Hash, P: SharedPointerKind> Hash for Stored<T, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl<T: Clone + fmt::Debug, P: SharedPointerKind> fmt::Debug for Stored<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::{HashMap, HashSet};
+use std::mem::size_of;
+use std::ops::Bound;
+
+use crate::shared::SharedPointerKind;
+use crate::{IntervalTree, Node};
+
+/// The shape and memory usage of a single IntervalTree
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TreeStats {
+    /// The number of nodes, which is also the number of intervals
+    pub nodes: usize,
+    /// The number of nodes on the longest path from the root to a leaf
+    pub height: usize,
+    /// The mean number of edges from the root to each node
+    pub average_depth: f64,
+    /// The approximate number of bytes allocated for nodes and bounds
+    pub bytes: usize,
+}
+
+/// How much of the memory used by a set of IntervalTrees is shared between them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SharedStats {
+    /// The number of nodes reachable from exactly one tree
+    pub unique_nodes: usize,
+    /// The number of nodes reachable from more than one tree
+    pub shared_nodes: usize,
+    /// The approximate number of bytes allocated for all distinct nodes and bounds
+    pub bytes: usize,
+}
+
+/// The bytes allocated for one shared value, including its reference counts
+fn allocation_size<V>() -> usize {
+    2 * size_of::<usize>() + size_of::<V>()
+}
+
+/// Collect the distinct bound allocations a node refers to
+///
+/// Bounds stored inline are part of the node's own allocation.
+fn add_bounds<T: Ord + Clone, P: SharedPointerKind>(
+    node: &Node<T, P>,
+    bounds: &mut HashSet<*const Bound<T>>,
+) {
+    if P::STORES_INLINE {
+        return;
+    }
+    for bound in [
+        &node.interval.low,
+        &node.interval.high,
+        &node.max,
+        &node.min,
+    ] {
+        bounds.insert(&**bound);
+    }
+}
+
+fn bytes<T: Ord + Clone, P: SharedPointerKind>(nodes: usize, bounds: usize) -> usize {
+    nodes * allocation_size::<Node<T, P>>() + bounds * allocation_size::<Bound<T>>()
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> IntervalTree<T, P> {
+    /// Return the shape and memory usage of the tree
+    ///
+    /// Memory is estimated from the sizes of the nodes and bounds the tree
+    /// refers to, counting each shared bound once. It does not include any
+    /// heap memory owned by the values of type `T`.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let tree : IntervalTree<u8> = (0..7)
+    ///     .map(|i| Interval::new(Included(i), Excluded(i + 1)))
+    ///     .collect();
+    ///
+    /// let stats = tree.stats();
+    /// assert_eq!(stats.nodes, 7);
+    /// assert_eq!(stats.height, 3);
+    /// assert_eq!(stats.average_depth, 10.0 / 7.0);
+    /// ```
+    pub fn stats(&self) -> TreeStats {
+        let mut nodes = 0;
+        let mut total_depth = 0;
+        let mut bounds = HashSet::new();
+        let mut stack = self
+            .root
+            .iter()
+            .map(|node| (&**node, 0))
+            .collect::<Vec<_>>();
+        while let Some((node, depth)) = stack.pop() {
+            nodes += 1;
+            total_depth += depth;
+            add_bounds(node, &mut bounds);
+            for child in node.left.iter().chain(&node.right) {
+                stack.push((child, depth + 1));
+            }
+        }
+        TreeStats {
+            nodes,
+            height: Node::height(&self.root),
+            average_depth: if nodes == 0 {
+                0.0
+            } else {
+                total_depth as f64 / nodes as f64
+            },
+            bytes: bytes::<T, P>(nodes, bounds.len()),
+        }
+    }
+
+    /// Return how many nodes a set of trees share, by pointer identity
+    ///
+    /// A subtree is walked again the second time it is reached, to mark
+    /// everything below it as shared, but never after that, so this takes
+    /// time proportional to the number of distinct nodes.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{IntervalTree, Interval};
+    /// let old : IntervalTree<u8> = (0..100)
+    ///     .map(|i| Interval::new(Included(i), Excluded(i + 1)))
+    ///     .collect();
+    /// let new = old.insert(Interval::new(Included(200), Unbounded));
+    ///
+    /// let stats = IntervalTree::shared_stats(&[old.clone(), new.clone()]);
+    /// assert_eq!(
+    ///     stats.unique_nodes + 2 * stats.shared_nodes,
+    ///     old.stats().nodes + new.stats().nodes
+    /// );
+    /// assert!(stats.unique_nodes < 20);
+    /// ```
+    pub fn shared_stats(trees: &[IntervalTree<T, P>]) -> SharedStats {
+        // Whether each node seen so far is reachable from more than one tree
+        let mut seen: HashMap<*const Node<T, P>, bool> = HashMap::new();
+        let mut bounds = HashSet::new();
+        for tree in trees {
+            let mut stack = tree.root.iter().map(|node| &**node).collect::<Vec<_>>();
+            while let Some(node) = stack.pop() {
+                match seen.get_mut(&(node as *const _)) {
+                    None => {
+                        seen.insert(node, false);
+                        add_bounds(node, &mut bounds);
+                    }
+                    // Everything below a shared node is already marked shared
+                    Some(true) => continue,
+                    Some(shared) => *shared = true,
+                }
+                for child in node.left.iter().chain(&node.right) {
+                    stack.push(child);
+                }
+            }
+        }
+        let shared_nodes = seen.values().filter(|&&shared| shared).count();
+        SharedStats {
+            unique_nodes: seen.len() - shared_nodes,
+            shared_nodes,
+            bytes: bytes::<T, P>(seen.len(), bounds.len()),
+        }
+    }
+}
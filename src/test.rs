@@ -3,6 +3,13 @@ use std::collections::HashSet;
 use crate::*;
 use quickcheck::*;
 
+/// Collect the intervals of a tree in sorted order
+fn sorted(tree: &IntervalTree<u8>) -> Vec<Interval<u8>> {
+    let mut intervals = tree.iter().collect::<Vec<_>>();
+    intervals.sort();
+    intervals
+}
+
 quickcheck! {
     fn test_insert(intervals : HashSet<Interval<u8>>) -> TestResult {
         let mut tree = IntervalTree::new();
@@ -95,6 +102,105 @@ quickcheck! {
     }
 }
 
+quickcheck! {
+    fn test_check_invariants(intervals : Vec<Interval<u8>>, to_remove : Vec<Interval<u8>>) -> TestResult {
+        let mut tree = IntervalTree::new();
+        for i in &intervals {
+            tree = tree.insert(i.clone());
+            if tree.check_invariants().is_err() {
+                return TestResult::failed();
+            }
+        }
+        for i in intervals.iter().chain(&to_remove) {
+            tree = tree.remove(i);
+            if tree.check_invariants().is_err() {
+                return TestResult::failed();
+            }
+        }
+        TestResult::passed()
+    }
+}
+
+#[test]
+fn check_invariants_detects_corruption() {
+    let tree = IntervalTree::new()
+        .insert(Interval::new(Included(1), Excluded(3)))
+        .insert(Interval::new(Included(2), Excluded(4)))
+        .insert(Interval::new(Included(5), Included(9)));
+    assert_eq!(tree.check_invariants(), Ok(()));
+
+    let root = tree.root.as_ref().unwrap();
+    let swapped = IntervalTree {
+        root: Some(Shared::new(Node::new(
+            root.interval.clone(),
+            root.right.clone(),
+            root.left.clone(),
+        ))),
+    };
+    assert_eq!(
+        swapped.check_invariants(),
+        Err(InvariantViolation::Unordered)
+    );
+
+    let mut stale = (**root).clone();
+    stale.max = Stored::new(Included(4));
+    let stale = IntervalTree {
+        root: Some(Shared::new(stale)),
+    };
+    assert_eq!(
+        stale.check_invariants(),
+        Err(InvariantViolation::MaxMismatch)
+    );
+
+    let mut tall = (**root).clone();
+    tall.height = 5;
+    let tall = IntervalTree {
+        root: Some(Shared::new(tall)),
+    };
+    assert_eq!(
+        tall.check_invariants(),
+        Err(InvariantViolation::HeightMismatch {
+            expected: 2,
+            found: 5
+        })
+    );
+
+    let empty = IntervalTree {
+        root: Some(Shared::new(Node::leaf(Interval::new(
+            Included(5),
+            Excluded(2),
+        )))),
+    };
+    assert_eq!(
+        empty.check_invariants(),
+        Err(InvariantViolation::EmptyInterval)
+    );
+}
+
+#[test]
+fn empty_intervals_are_not_inserted() {
+    let empty = Interval::new(Included(5), Excluded(2));
+    let tree = IntervalTree::new().insert(Interval::new(Included(1), Excluded(3)));
+
+    let (same, added) = tree.insert_full(empty.clone());
+    assert!(!added);
+    assert!(same.ptr_eq(&tree));
+    assert_eq!(IntervalTree::new().insert(empty.clone()).iter().count(), 0);
+
+    let collected = vec![empty.clone(), Interval::new(Included(1), Excluded(3))]
+        .into_iter()
+        .collect::<IntervalTree<i32>>();
+    assert_eq!(collected.check_invariants(), Ok(()));
+    assert_eq!(collected.iter().count(), 1);
+
+    let batched = tree.apply_batch(vec![Change::Added(empty.clone())]);
+    assert!(batched.ptr_eq(&tree));
+
+    let btree = IntervalBTree::new().insert(empty.clone());
+    assert_eq!(btree.check_invariants(), Ok(()));
+    assert_eq!(btree.iter().count(), 0);
+}
+
 #[cfg(feature = "arc")]
 #[test]
 fn interval_tree_is_send() {
@@ -115,3 +221,935 @@ fn interval_tree_is_send() {
         HashSet::from([Interval::new(Included(10), Excluded(50))])
     )
 }
+
+quickcheck! {
+    fn test_display_from_str_round_trip(interval: Interval<i8>) -> TestResult {
+        let parsed = interval.to_string().parse::<Interval<i8>>();
+        TestResult::from_bool(parsed == Ok(interval))
+    }
+}
+
+#[test]
+fn from_str_reports_errors() {
+    assert_eq!(
+        "{1, 3)".parse::<Interval<u8>>(),
+        Err(ParseIntervalError::InvalidLowBracket(Some('{')))
+    );
+    assert_eq!(
+        "[1, 3".parse::<Interval<u8>>(),
+        Err(ParseIntervalError::InvalidHighBracket(Some('3')))
+    );
+    assert_eq!(
+        "".parse::<Interval<u8>>(),
+        Err(ParseIntervalError::InvalidLowBracket(None))
+    );
+    assert_eq!(
+        "[1 3)".parse::<Interval<u8>>(),
+        Err(ParseIntervalError::MissingSeparator)
+    );
+    assert_eq!(
+        "[a,b, c)".parse::<Interval<String>>(),
+        Err(ParseIntervalError::AmbiguousSeparator)
+    );
+    assert_eq!(
+        "[-inf, 3)".parse::<Interval<u8>>(),
+        Err(ParseIntervalError::ClosedInfinity)
+    );
+    assert!(matches!(
+        "[a, 3)".parse::<Interval<u8>>(),
+        Err(ParseIntervalError::InvalidLow(_))
+    ));
+    assert!(matches!(
+        "[1, 300)".parse::<Interval<u8>>(),
+        Err(ParseIntervalError::InvalidHigh(_))
+    ));
+    assert_eq!(
+        "(3, 3]".parse::<Interval<u8>>(),
+        Err(ParseIntervalError::Empty)
+    );
+    assert_eq!(
+        " ( -inf , inf ) ".parse::<Interval<u8>>(),
+        Ok(Interval::new(Unbounded, Unbounded))
+    );
+}
+
+#[test]
+fn pointer_kinds_can_be_mixed() {
+    let local: IntervalTree<i32, RcK> = IntervalTree::new_with_ptr_kind()
+        .insert(Interval::new_with_ptr_kind(Included(1), Excluded(5)));
+    let sync = IntervalTreeSync::new_sync()
+        .insert(IntervalSync::new_sync(Included(1), Excluded(5)))
+        .insert(IntervalSync::new_sync(Included(10), Excluded(50)));
+    let handle = std::thread::spawn(move || sync.query_point(&20).collect::<Vec<_>>());
+    assert_eq!(
+        handle.join().expect("Joining thread"),
+        vec![IntervalSync::new_sync(Included(10), Excluded(50))]
+    );
+    assert_eq!(
+        local.query_point(&2).collect::<Vec<_>>(),
+        vec![Interval::new_with_ptr_kind(Included(1), Excluded(5))]
+    );
+}
+
+#[test]
+fn locked_interval_tree_applies_concurrent_updates() {
+    let handle = std::sync::Arc::new(LockedIntervalTree::new());
+    let snapshot = handle.load();
+    let writers = (0..8)
+        .map(|i| {
+            let handle = handle.clone();
+            std::thread::spawn(move || {
+                for j in 0..50 {
+                    let start = i * 100 + j;
+                    handle.insert(IntervalSync::new_sync(Included(start), Excluded(start + 1)));
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for writer in writers {
+        writer.join().expect("Joining thread");
+    }
+
+    let tree = handle.load();
+    assert_eq!(snapshot.iter().count(), 0);
+    assert_eq!(tree.iter().count(), 400);
+    assert_eq!(tree.check_invariants(), Ok(()));
+
+    let removed = handle.remove(&IntervalSync::new_sync(Included(0), Excluded(1)));
+    assert_eq!(removed.iter().count(), 399);
+    assert!(handle
+        .compare_and_swap(&tree, IntervalTree::new_sync())
+        .is_err());
+    assert!(handle
+        .compare_and_swap(&removed, IntervalTree::new_sync())
+        .is_ok());
+    assert_eq!(handle.load().iter().count(), 0);
+
+    let inline: std::sync::Arc<LockedIntervalTree<u32, InlineArcK>> = Default::default();
+    let writer = {
+        let inline = inline.clone();
+        std::thread::spawn(move || {
+            inline.insert(Interval::new_with_ptr_kind(Included(1), Excluded(5)));
+        })
+    };
+    writer.join().expect("Joining thread");
+    assert_eq!(inline.load().query_point(&3).count(), 1);
+}
+
+quickcheck! {
+    fn test_from_iter(intervals : Vec<Interval<u8>>) -> TestResult {
+        let tree = intervals.iter().cloned().collect::<IntervalTree<u8>>();
+        let expected = intervals.into_iter().collect::<HashSet<_>>();
+        let collected = tree.iter().collect::<HashSet<_>>();
+        TestResult::from_bool(tree.check_invariants().is_ok() && collected == expected)
+    }
+}
+
+quickcheck! {
+    fn test_union(a : Vec<Interval<u8>>, b : Vec<Interval<u8>>) -> TestResult {
+        let mut tree_a = IntervalTree::new();
+        for i in &a {
+            tree_a = tree_a.insert(i.clone());
+        }
+        let tree_b = b.iter().cloned().collect::<IntervalTree<u8>>();
+        let union = tree_a.union(&tree_b);
+
+        let expected = a.into_iter().chain(b).collect::<HashSet<_>>();
+        let collected = union.iter().collect::<HashSet<_>>();
+        TestResult::from_bool(union.check_invariants().is_ok() && collected == expected)
+    }
+}
+
+#[cfg(feature = "rayon")]
+quickcheck! {
+    fn test_par_query_interval(intervals : Vec<Interval<u8>>, query : Interval<u8>) -> TestResult {
+        use rayon::prelude::*;
+
+        let tree = intervals.iter().cloned().collect::<IntervalTree<u8>>();
+        let sync = intervals
+            .iter()
+            .map(|i| IntervalSync::new_sync(*i.low(), *i.high()))
+            .collect::<Vec<_>>();
+        let sync_query = IntervalSync::new_sync(*query.low(), *query.high());
+        let par_tree = sync.into_par_iter().collect::<IntervalTreeSync<u8>>();
+
+        let inline_tree = intervals
+            .iter()
+            .map(|i| Interval::new_with_ptr_kind(*i.low(), *i.high()))
+            .collect::<Vec<Interval<u8, InlineArcK>>>()
+            .into_par_iter()
+            .collect::<IntervalTree<u8, InlineArcK>>();
+        let inline_query = Interval::new_with_ptr_kind(*query.low(), *query.high());
+
+        let mut expected = tree
+            .query_interval(&query)
+            .map(|i| IntervalSync::new_sync(*i.low(), *i.high()))
+            .collect::<Vec<_>>();
+        let mut collected = par_tree.par_query_interval(&sync_query).collect::<Vec<_>>();
+        let mut inline_collected = inline_tree
+            .par_query_interval(&inline_query)
+            .map(|i| IntervalSync::new_sync(*i.low(), *i.high()))
+            .collect::<Vec<_>>();
+        expected.sort();
+        collected.sort();
+        inline_collected.sort();
+        TestResult::from_bool(
+            par_tree.check_invariants().is_ok()
+                && inline_tree.check_invariants().is_ok()
+                && collected == expected
+                && inline_collected == expected
+        )
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn par_union_matches_union() {
+    let a = (0..5000u32)
+        .map(|i| IntervalSync::new_sync(Included(i * 3), Excluded(i * 3 + 7)))
+        .collect::<IntervalTreeSync<u32>>();
+    let b = (0..5000u32)
+        .map(|i| IntervalSync::new_sync(Included(i * 5), Excluded(i * 5 + 2)))
+        .collect::<IntervalTreeSync<u32>>();
+    let union = a.par_union(&b);
+    assert_eq!(union.check_invariants(), Ok(()));
+    assert_eq!(
+        union.iter().collect::<Vec<_>>(),
+        a.union(&b).iter().collect::<Vec<_>>()
+    );
+}
+
+quickcheck! {
+    fn test_query_interval_ref(intervals : Vec<Interval<u8>>, query : Interval<u8>) -> TestResult {
+        let tree = intervals.into_iter().collect::<IntervalTree<u8>>();
+        let owned = tree.query_interval(&query).collect::<Vec<_>>();
+        let borrowed = tree.query_interval_ref(&query).cloned().collect::<Vec<_>>();
+        let all = tree.iter_ref().cloned().collect::<Vec<_>>();
+        TestResult::from_bool(owned == borrowed && all == tree.iter().collect::<Vec<_>>())
+    }
+}
+
+quickcheck! {
+    fn test_overlap_join(a : Vec<Interval<u8>>, b : Vec<Interval<u8>>) -> TestResult {
+        let tree_a = a.into_iter().collect::<IntervalTree<u8>>();
+        let tree_b = b.into_iter().collect::<IntervalTree<u8>>();
+
+        let mut expected = HashSet::new();
+        for x in tree_a.iter() {
+            for y in tree_b.query_interval(&x) {
+                expected.insert((x.clone(), y));
+            }
+        }
+
+        let collected = tree_a.overlap_join(&tree_b).collect::<Vec<_>>();
+        let no_duplicates = collected.len() == expected.len();
+        TestResult::from_bool(no_duplicates && collected.into_iter().collect::<HashSet<_>>() == expected)
+    }
+}
+
+fn distance_to_point(interval: &Interval<u8>, point: u8) -> u8 {
+    let before = match interval.low() {
+        Included(low) if point < *low => Some(low - point),
+        Excluded(low) if point <= *low => Some(low - point),
+        _ => None,
+    };
+    let after = match interval.high() {
+        Included(high) if point > *high => Some(point - high),
+        Excluded(high) if point >= *high => Some(point - high),
+        _ => None,
+    };
+    before.or(after).unwrap_or(0)
+}
+
+quickcheck! {
+    fn test_nearest_before_after(intervals : Vec<Interval<u8>>, point : u8) -> TestResult {
+        let tree = intervals.into_iter().collect::<IntervalTree<u8>>();
+
+        let before = tree
+            .iter()
+            .filter(|i| matches!(i.high(), Included(h) | Excluded(h) if *h <= point))
+            .max_by(|a, b| interval::high_bound_cmp(a.high(), b.high()).then(a.cmp(b)));
+        let after = tree
+            .iter()
+            .filter(|i| matches!(i.low(), Included(l) | Excluded(l) if *l >= point))
+            .min();
+
+        TestResult::from_bool(tree.nearest_before(&point) == before && tree.nearest_after(&point) == after)
+    }
+}
+
+quickcheck! {
+    fn test_k_nearest(intervals : Vec<Interval<u8>>, point : u8, k : usize) -> TestResult {
+        let k = k % 8;
+        let tree = intervals.into_iter().collect::<IntervalTree<u8>>();
+
+        let mut expected = tree.iter().map(|i| distance_to_point(&i, point)).collect::<Vec<_>>();
+        expected.sort();
+        expected.truncate(k);
+
+        let nearest = tree.k_nearest(&point, k);
+        let distances = nearest.iter().map(|i| distance_to_point(i, point)).collect::<Vec<_>>();
+        let distinct = nearest.iter().collect::<HashSet<_>>().len() == nearest.len();
+        TestResult::from_bool(distinct && distances == expected)
+    }
+}
+
+quickcheck! {
+    fn test_navigation(intervals : Vec<Interval<u8>>, probe : Interval<u8>) -> TestResult {
+        let tree = intervals.into_iter().collect::<IntervalTree<u8>>();
+        let mut sorted = tree.iter_ref().cloned().collect::<Vec<_>>();
+        sorted.sort();
+
+        let contains = tree.contains(&probe) == sorted.contains(&probe);
+        let first_last = tree.first() == sorted.first().cloned() && tree.last() == sorted.last().cloned();
+        let predecessor = tree.predecessor(&probe) == sorted.iter().rev().find(|i| **i < probe).cloned();
+        let successor = tree.successor(&probe) == sorted.iter().find(|i| **i > probe).cloned();
+
+        TestResult::from_bool(contains && first_last && predecessor && successor)
+    }
+}
+
+quickcheck! {
+    fn test_pop_first_last(intervals : Vec<Interval<u8>>) -> TestResult {
+        let tree = intervals.into_iter().collect::<IntervalTree<u8>>();
+        let mut sorted = tree.iter_ref().cloned().collect::<Vec<_>>();
+        sorted.sort();
+
+        let mut popped = Vec::new();
+        let mut remaining = tree.clone();
+        while let Some((first, rest)) = remaining.pop_first() {
+            if rest.check_invariants().is_err() {
+                return TestResult::failed();
+            }
+            popped.push(first);
+            remaining = rest;
+        }
+        let mut popped_back = Vec::new();
+        let mut remaining = tree;
+        while let Some((last, rest)) = remaining.pop_last() {
+            if rest.check_invariants().is_err() {
+                return TestResult::failed();
+            }
+            popped_back.push(last);
+            remaining = rest;
+        }
+        popped_back.reverse();
+
+        TestResult::from_bool(popped == sorted && popped_back == sorted)
+    }
+}
+
+quickcheck! {
+    fn test_cursor(intervals : Vec<Interval<u8>>, probe : Interval<u8>) -> TestResult {
+        let tree = intervals.into_iter().collect::<IntervalTree<u8>>();
+        let mut sorted = tree.iter().collect::<Vec<_>>();
+        sorted.sort();
+
+        let mut forward = Vec::new();
+        let mut cursor = tree.cursor_front();
+        while let Some(current) = cursor.current() {
+            forward.push(current.clone());
+            cursor.move_next();
+        }
+        let mut backward = Vec::new();
+        let mut cursor = tree.cursor_back();
+        while let Some(current) = cursor.current() {
+            backward.push(current.clone());
+            cursor.move_prev();
+        }
+        backward.reverse();
+
+        let mut cursor = tree.cursor_front();
+        let seek = cursor.seek(&probe).cloned() == sorted.iter().find(|i| **i >= probe).cloned();
+        let prev = cursor.clone().move_prev().cloned() == sorted.iter().rev().find(|i| **i < probe).cloned();
+        let seek_low = cursor.seek_low(probe.low()).cloned()
+            == sorted.iter().find(|i| interval::low_bound_cmp(i.low(), probe.low()) != Ordering::Less).cloned();
+
+        TestResult::from_bool(forward == sorted && backward == sorted && seek && prev && seek_low)
+    }
+}
+
+fn bound_value(bound: &Bound<u8>) -> Option<u8> {
+    match bound {
+        Included(x) | Excluded(x) => Some(*x),
+        Unbounded => None,
+    }
+}
+
+quickcheck! {
+    fn test_range_by_low_high(intervals : Vec<Interval<u8>>, start : u8, end : u8) -> TestResult {
+        let tree = intervals.into_iter().collect::<IntervalTree<u8>>();
+        let mut sorted = tree.iter().collect::<Vec<_>>();
+        sorted.sort();
+
+        let by_low = tree.range_by_low(start..end).collect::<Vec<_>>();
+        let expected_low = sorted
+            .iter()
+            .filter(|i| bound_value(i.low()).is_some_and(|low| (start..end).contains(&low)))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut by_high = tree.range_by_high(start..=end).collect::<Vec<_>>();
+        by_high.sort();
+        let expected_high = sorted
+            .iter()
+            .filter(|i| bound_value(i.high()).is_some_and(|high| (start..=end).contains(&high)))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut unbounded = tree.range_by_high(..).collect::<Vec<_>>();
+        unbounded.sort();
+
+        TestResult::from_bool(
+            by_low == expected_low
+                && by_high == expected_high
+                && tree.range_by_low(..).collect::<Vec<_>>() == sorted
+                && unbounded == sorted
+        )
+    }
+}
+
+quickcheck! {
+    fn test_insert_remove_full(intervals : Vec<Interval<u8>>, to_remove : Vec<Interval<u8>>) -> TestResult {
+        let mut tree = IntervalTree::new();
+        let mut expected = HashSet::new();
+        for i in &intervals {
+            let (new_tree, added) = tree.insert_full(i.clone());
+            if added != expected.insert(i.clone()) || added == new_tree.ptr_eq(&tree) {
+                return TestResult::failed();
+            }
+            tree = new_tree;
+        }
+        for i in &to_remove {
+            let (new_tree, removed) = tree.remove_full(i);
+            let was_present = expected.remove(i);
+            if removed.as_ref() != was_present.then_some(i) || was_present == new_tree.ptr_eq(&tree) {
+                return TestResult::failed();
+            }
+            tree = new_tree;
+        }
+
+        let collected = tree.iter().collect::<HashSet<_>>();
+        TestResult::from_bool(collected == expected)
+    }
+}
+
+quickcheck! {
+    fn test_filter_partition(intervals : Vec<Interval<u8>>, threshold : u8) -> TestResult {
+        let tree = intervals.into_iter().collect::<IntervalTree<u8>>();
+        let pred = |i: &Interval<u8>| matches!(i.low(), Included(x) | Excluded(x) if *x < threshold);
+        let (expected_in, expected_out): (Vec<_>, Vec<_>) = sorted(&tree).into_iter().partition(pred);
+
+        let filtered = tree.filter(pred);
+        let mut retained = tree.clone();
+        retained.retain(pred);
+        let (kept, rejected) = tree.partition(pred);
+
+        TestResult::from_bool(
+            filtered.check_invariants().is_ok()
+                && kept.check_invariants().is_ok()
+                && rejected.check_invariants().is_ok()
+                && sorted(&filtered) == expected_in
+                && sorted(&retained) == expected_in
+                && sorted(&kept) == expected_in
+                && sorted(&rejected) == expected_out
+                && tree.filter(|_| true).ptr_eq(&tree)
+        )
+    }
+}
+
+quickcheck! {
+    fn test_remove_overlapping(intervals : Vec<Interval<u8>>, window : Interval<u8>) -> TestResult {
+        let tree = intervals.into_iter().collect::<IntervalTree<u8>>();
+        let all = sorted(&tree);
+
+        let (overlap_in, overlap_out): (Vec<_>, Vec<_>) =
+            all.iter().cloned().partition(|i| window.overlaps(i));
+        let (without_overlapping, removed_overlapping) = tree.remove_overlapping(&window);
+
+        let (contained_in, contained_out): (Vec<_>, Vec<_>) =
+            all.iter().cloned().partition(|i| window.contains(i));
+        let (without_contained, removed_contained) = tree.remove_contained_in(&window);
+
+        TestResult::from_bool(
+            without_overlapping.check_invariants().is_ok()
+                && without_contained.check_invariants().is_ok()
+                && removed_overlapping == overlap_in
+                && sorted(&without_overlapping) == overlap_out
+                && removed_contained == contained_in
+                && sorted(&without_contained) == contained_out
+                && removed_overlapping.is_empty() == without_overlapping.ptr_eq(&tree)
+        )
+    }
+}
+
+quickcheck! {
+    fn test_clip(intervals : Vec<Interval<u8>>, window : Interval<u8>, point : u8) -> TestResult {
+        let tree = intervals.into_iter().collect::<IntervalTree<u8>>();
+        let clipped_by = |window: &Interval<u8>| {
+            let mut v = tree
+                .iter()
+                .filter_map(|i| i.get_overlap(window))
+                .collect::<Vec<_>>();
+            v.sort();
+            v.dedup();
+            v
+        };
+
+        TestResult::from_bool(
+            sorted(&tree.clip(&window)) == clipped_by(&window)
+                && sorted(&tree.truncate_before(&point))
+                    == clipped_by(&Interval::new(Included(point), Unbounded))
+                && sorted(&tree.truncate_after(&point))
+                    == clipped_by(&Interval::new(Unbounded, Included(point)))
+        )
+    }
+}
+
+quickcheck! {
+    fn test_map_bounds(intervals : Vec<Interval<u8>>) -> TestResult {
+        let tree = intervals.into_iter().collect::<IntervalTree<u8>>();
+        let mut sorted = tree.iter().collect::<Vec<_>>();
+        sorted.sort();
+        let values = sorted
+            .iter()
+            .flat_map(|i| [bound_value(i.low()), bound_value(i.high())])
+            .flatten()
+            .collect::<HashSet<_>>();
+        // Strictly monotonic maps keep every interval, so nothing is dropped
+        let check_strict = |f: &dyn Fn(&u8) -> u16, reversed: bool| {
+            let mapped = tree.map_bounds(f);
+            let mut collected = mapped.iter().collect::<Vec<_>>();
+            collected.sort();
+            let mut expected = sorted
+                .iter()
+                .map(|i| if reversed { i.map_reversed(f) } else { i.map(f) })
+                .collect::<Vec<_>>();
+            expected.sort();
+            mapped.check_invariants().is_ok() && collected == expected
+        };
+        // Other maps may merge intervals or make them empty, which must be dropped
+        let check_lossy = |f: &dyn Fn(&u8) -> u16| {
+            let mapped = tree.map_bounds(f);
+            let images = sorted
+                .iter()
+                .flat_map(|i| [i.map(f), i.map_reversed(f)])
+                .collect::<Vec<_>>();
+            mapped.check_invariants().is_ok()
+                && mapped.iter().count() <= sorted.len()
+                && mapped.iter().all(|i| i.overlaps(&i) && images.contains(&i))
+        };
+
+        TestResult::from_bool(
+            check_strict(&|x| u16::from(*x) * 3 + 1, false)
+                && check_strict(&|x| 255 - u16::from(*x), values.len() > 1)
+                && check_lossy(&|x| u16::from(*x) % 7)
+                && check_lossy(&|x| u16::from(*x) / 4)
+        )
+    }
+}
+
+#[test]
+fn versioned_interval_tree_tracks_history() {
+    let point = |i: u8| Interval::new(Included(i), Included(i));
+    let mut history: VersionedIntervalTree<u8> = VersionedIntervalTree::new();
+    let root = history.current_version();
+    let one = history.update("one", |tree| tree.insert(point(1)));
+    let two = history.update("two", |tree| tree.insert(point(2)));
+    assert_eq!(history.version(two).map(|v| v.label()), Some("two"));
+
+    assert_eq!(history.undo().map(|tree| tree.iter().count()), Some(1));
+    assert_eq!(history.current_version(), one);
+    let branch = history.update("branch", |tree| tree.insert(point(3)));
+    assert!(history.redo().is_none());
+    assert!(history.at_version(two).is_some());
+
+    history.undo();
+    history.undo();
+    assert_eq!(history.current_version(), root);
+    assert!(history.undo().is_none());
+    history.redo();
+    history.redo();
+    assert_eq!(history.current_version(), branch);
+
+    history.prune(|number, _| number != one);
+    assert!(history.at_version(one).is_none());
+    assert_eq!(history.version(branch).unwrap().parent(), Some(root));
+    assert_eq!(history.version(two).unwrap().parent(), Some(root));
+    assert!(history.checkout(one).is_none());
+    assert_eq!(
+        history.checkout(two).map(|tree| tree.iter().count()),
+        Some(2)
+    );
+    assert_eq!(history.current_version(), two);
+}
+
+quickcheck! {
+    fn test_diff(base : Vec<Interval<u8>>, added : Vec<Interval<u8>>, removed : Vec<Interval<u8>>) -> TestResult {
+        let old = base.iter().cloned().collect::<IntervalTree<u8>>();
+        let mut new = old.clone();
+        for i in &added {
+            new = new.insert(i.clone());
+        }
+        for i in removed.iter().chain(base.iter().step_by(3)) {
+            new = new.remove(i);
+        }
+
+        let old_set = old.iter().collect::<HashSet<_>>();
+        let new_set = new.iter().collect::<HashSet<_>>();
+        let mut expected = old_set
+            .difference(&new_set)
+            .map(|i| (i.clone(), false))
+            .chain(new_set.difference(&old_set).map(|i| (i.clone(), true)))
+            .collect::<Vec<_>>();
+        expected.sort();
+
+        let changes = old
+            .diff(&new)
+            .map(|change| match change {
+                Change::Added(i) => (i, true),
+                Change::Removed(i) => (i, false),
+            })
+            .collect::<Vec<_>>();
+
+        TestResult::from_bool(changes == expected && new.diff(&new).next().is_none())
+    }
+}
+
+quickcheck! {
+    fn test_merge3(base : Vec<Interval<u8>>, ours : Vec<Interval<u8>>, theirs : Vec<Interval<u8>>) -> TestResult {
+        let base_tree = base.iter().cloned().collect::<IntervalTree<u8>>();
+        // Each side removes a different but overlapping selection of base intervals
+        let derive = |step: usize, added: &[Interval<u8>]| {
+            let mut tree = base_tree.clone();
+            for i in base.iter().step_by(step) {
+                tree = tree.remove(i);
+            }
+            for i in added {
+                tree = tree.insert(i.clone());
+            }
+            tree
+        };
+        let ours_tree = derive(2, &ours);
+        let theirs_tree = derive(3, &theirs);
+
+        let set = |tree: &IntervalTree<u8>| tree.iter().collect::<HashSet<_>>();
+        let (base_set, ours_set, theirs_set) = (set(&base_tree), set(&ours_tree), set(&theirs_tree));
+        let mut expected = base_set
+            .iter()
+            .filter(|i| ours_set.contains(i) && theirs_set.contains(i))
+            .cloned()
+            .collect::<HashSet<_>>();
+        expected.extend(ours_set.difference(&base_set).cloned());
+        expected.extend(theirs_set.difference(&base_set).cloned());
+
+        let replacements = |side: &HashSet<Interval<u8>>, interval: &Interval<u8>| {
+            let mut v = side
+                .difference(&base_set)
+                .filter(|i| i.overlaps(interval))
+                .cloned()
+                .collect::<Vec<_>>();
+            v.sort();
+            v
+        };
+        let expected_conflicts = base_set
+            .iter()
+            .filter(|i| !ours_set.contains(i) && !theirs_set.contains(i))
+            .filter(|i| replacements(&ours_set, i) != replacements(&theirs_set, i))
+            .count();
+
+        let mut conflicts = 0;
+        let merged = IntervalTree::merge3(&base_tree, &ours_tree, &theirs_tree, |_| {
+            conflicts += 1;
+            Resolution::Union
+        });
+        let restored = IntervalTree::merge3(&base_tree, &ours_tree, &theirs_tree, |_| Resolution::Base);
+
+        TestResult::from_bool(
+            set(&merged) == expected
+                && conflicts == expected_conflicts
+                && restored.check_invariants().is_ok()
+        )
+    }
+}
+
+#[test]
+fn observable_interval_tree_notifies_subscribers() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let interval = |low: u8, high: u8| Interval::new(Included(low), Excluded(high));
+    let mut tree: ObservableIntervalTree<u8> = ObservableIntervalTree::new();
+    let all = Rc::new(RefCell::new(Vec::new()));
+    let windowed = Rc::new(RefCell::new(Vec::new()));
+
+    let log = all.clone();
+    let everything = tree.subscribe(move |change| log.borrow_mut().push(change.clone()));
+    let log = windowed.clone();
+    tree.subscribe_window(interval(10, 20), move |change| {
+        log.borrow_mut().push(change.clone())
+    });
+
+    assert!(tree.insert(interval(1, 5)));
+    assert!(tree.insert(interval(12, 15)));
+    assert!(!tree.insert(interval(12, 15)));
+    assert_eq!(tree.remove(&interval(30, 40)), None);
+    tree.update(|tree| tree.insert(interval(18, 25)).remove(&interval(1, 5)));
+    assert!(tree.unsubscribe(everything));
+    assert!(!tree.unsubscribe(everything));
+    tree.remove(&interval(12, 15));
+
+    assert_eq!(
+        *all.borrow(),
+        vec![
+            Change::Added(interval(1, 5)),
+            Change::Added(interval(12, 15)),
+            Change::Removed(interval(1, 5)),
+            Change::Added(interval(18, 25)),
+        ]
+    );
+    assert_eq!(
+        *windowed.borrow(),
+        vec![
+            Change::Added(interval(12, 15)),
+            Change::Added(interval(18, 25)),
+            Change::Removed(interval(12, 15)),
+        ]
+    );
+    assert_eq!(tree.tree().iter().count(), 1);
+}
+
+quickcheck! {
+    fn test_stats(intervals : Vec<Interval<u8>>, extra : Vec<Interval<u8>>) -> TestResult {
+        let old = intervals.into_iter().collect::<IntervalTree<u8>>();
+        let mut new = old.clone();
+        for i in extra {
+            new = new.insert(i);
+        }
+        let (old_stats, new_stats) = (old.stats(), new.stats());
+        let shared = IntervalTree::shared_stats(&[old.clone(), new.clone()]);
+        let alone = IntervalTree::shared_stats(std::slice::from_ref(&old));
+
+        TestResult::from_bool(
+            old_stats.nodes == old.iter().count()
+                && new_stats.nodes == new.iter().count()
+                && shared.unique_nodes + 2 * shared.shared_nodes == old_stats.nodes + new_stats.nodes
+                && (!new.ptr_eq(&old) || shared.shared_nodes == old_stats.nodes)
+                && alone.unique_nodes == old_stats.nodes
+                && alone.shared_nodes == 0
+                && alone.bytes == old_stats.bytes
+        )
+    }
+}
+
+quickcheck! {
+    fn test_interval_btree(intervals : Vec<Interval<u8>>, to_remove : Vec<Interval<u8>>, query : Interval<u8>) -> TestResult {
+        let mut btree = IntervalBTree::new();
+        let mut tree = IntervalTree::new();
+        for i in &intervals {
+            btree = btree.insert(i.clone());
+            tree = tree.insert(i.clone());
+        }
+        for i in to_remove.iter().chain(intervals.iter().step_by(2)) {
+            btree = btree.remove(i);
+            tree = tree.remove(i);
+        }
+        let bulk = intervals.into_iter().collect::<IntervalBTree<u8>>();
+
+        let mut expected = tree.query_interval(&query).collect::<Vec<_>>();
+        expected.sort();
+        let mut all = tree.iter().collect::<Vec<_>>();
+        all.sort();
+
+        TestResult::from_bool(
+            btree.check_invariants().is_ok()
+                && bulk.check_invariants().is_ok()
+                && btree.query_interval(&query).collect::<Vec<_>>() == expected
+                && btree.query_interval_ref(&query).eq(expected.iter())
+                && btree.iter().collect::<Vec<_>>() == all
+                && btree.iter_ref().eq(all.iter())
+                && btree.first() == all.first().cloned()
+                && btree.last() == all.last().cloned()
+                && all.iter().all(|i| btree.contains(i))
+                && to_remove.iter().all(|i| btree.contains(i) == tree.contains(i))
+        )
+    }
+}
+
+#[test]
+fn interval_btree_splits_and_merges_nodes() {
+    let interval =
+        |i: u32| Interval::new(Included(i % 97 * 100 + i), Excluded(i % 97 * 100 + i + 50));
+    let mut btree = IntervalBTree::new();
+    for i in 0..5000 {
+        btree = btree.insert(interval(i));
+    }
+    let bulk = (0..5000).map(interval).collect::<IntervalBTree<u32>>();
+    assert_eq!(btree.check_invariants(), Ok(()));
+    assert_eq!(bulk.check_invariants(), Ok(()));
+    assert_eq!(
+        btree.iter().collect::<Vec<_>>(),
+        bulk.iter().collect::<Vec<_>>()
+    );
+
+    for i in (0..5000).filter(|i| i % 7 != 0) {
+        btree = btree.remove(&interval(i));
+        assert_eq!(btree.check_invariants(), Ok(()));
+    }
+    let expected = (0..5000)
+        .filter(|i| i % 7 == 0)
+        .map(interval)
+        .filter(|i| i.contains(&Interval::new(Included(5000), Included(5000))))
+        .count();
+    assert_eq!(btree.query_point(&5000).count(), expected);
+    assert_eq!(
+        btree.iter().count(),
+        (0..5000).filter(|i| i % 7 == 0).count()
+    );
+}
+
+quickcheck! {
+    fn test_inline_bounds(intervals : Vec<Interval<u8>>, to_remove : Vec<Interval<u8>>, query : Interval<u8>) -> TestResult {
+        let inline = |i: &Interval<u8>| -> Interval<u8, InlineRcK> {
+            Interval::new_with_ptr_kind(*i.low(), *i.high())
+        };
+        let mut tree = IntervalTree::new();
+        let mut inline_tree: IntervalTree<u8, InlineRcK> = IntervalTree::new_with_ptr_kind();
+        for i in &intervals {
+            tree = tree.insert(i.clone());
+            inline_tree = inline_tree.insert(inline(i));
+        }
+        for i in &to_remove {
+            tree = tree.remove(i);
+            inline_tree = inline_tree.remove(&inline(i));
+        }
+
+        let mut expected = tree.query_interval(&query).map(|i| inline(&i)).collect::<Vec<_>>();
+        expected.sort();
+        let mut actual = inline_tree.query_interval(&inline(&query)).collect::<Vec<_>>();
+        actual.sort();
+
+        let (stats, inline_stats) = (tree.stats(), inline_tree.stats());
+        TestResult::from_bool(
+            inline_tree.check_invariants().is_ok()
+                && actual == expected
+                && inline_stats.nodes == stats.nodes
+                && inline_stats.bytes <= stats.bytes
+        )
+    }
+}
+
+#[test]
+fn inline_bounds_shrink_intervals() {
+    assert_eq!(
+        std::mem::size_of::<Interval<u32, InlineRcK>>(),
+        2 * std::mem::size_of::<Bound<u32>>()
+    );
+    assert_eq!(
+        std::mem::size_of::<Interval<u32, RcK>>(),
+        2 * std::mem::size_of::<usize>()
+    );
+
+    let tree: IntervalTree<u32, InlineRcK> = (0..1000)
+        .map(|i| Interval::new_with_ptr_kind(Included(i), Excluded(i + 1)))
+        .collect();
+    let shared: IntervalTree<u32, RcK> = (0..1000)
+        .map(|i| Interval::new_with_ptr_kind(Included(i), Excluded(i + 1)))
+        .collect();
+    assert!(tree.stats().bytes < shared.stats().bytes);
+}
+
+quickcheck! {
+    fn test_path_buffer(intervals : Vec<Interval<u8>>, to_remove : Vec<Interval<u8>>) -> TestResult {
+        let mut buffer = PathBuffer::new();
+        let mut tree = IntervalTree::new();
+        let mut buffered = IntervalTree::new();
+        for i in &intervals {
+            let (next, added) = buffered.insert_with_buffer(i.clone(), &mut buffer);
+            if added == tree.iter().any(|j| &j == i) {
+                return TestResult::failed();
+            }
+            tree = tree.insert(i.clone());
+            buffered = next;
+        }
+        for i in to_remove.iter().chain(intervals.iter().step_by(2)) {
+            let (next, removed) = buffered.remove_with_buffer(i, &mut buffer);
+            if removed.as_ref() != tree.iter().find(|j| j == i).as_ref() {
+                return TestResult::failed();
+            }
+            tree = tree.remove(i);
+            buffered = next;
+        }
+
+        let mut expected = tree.iter().collect::<Vec<_>>();
+        expected.sort();
+        let mut actual = buffered.iter().collect::<Vec<_>>();
+        actual.sort();
+        TestResult::from_bool(
+            buffered.check_invariants().is_ok() && buffer.steps.is_empty() && actual == expected
+        )
+    }
+}
+
+// A recursive insert or remove needs about 26 KiB of stack to reach the
+// bottom of a tree of a million intervals in an unoptimized build, so this
+// overflows the smallest stack a thread can have unless updates iterate.
+// Optimized builds fit either way. The invariant checks recurse themselves.
+#[cfg(not(feature = "debug-invariants"))]
+#[test]
+fn updates_run_on_small_stacks() {
+    let n = 1 << 20;
+    let tree = (0..n)
+        .map(|i| IntervalSync::new_sync(Included(2 * i), Excluded(2 * i + 1)))
+        .collect::<IntervalTree<u32, ArcK>>();
+    let handle = std::thread::Builder::new()
+        .stack_size(16 * 1024)
+        .spawn(move || {
+            let mut tree = tree;
+            for i in (0..n).step_by(4096) {
+                tree = tree.insert(IntervalSync::new_sync(
+                    Included(2 * i + 1),
+                    Excluded(2 * i + 2),
+                ));
+                tree = tree.remove(&IntervalSync::new_sync(
+                    Included(2 * i),
+                    Excluded(2 * i + 1),
+                ));
+            }
+            tree.iter().count()
+        })
+        .expect("Spawning thread");
+    assert_eq!(handle.join().expect("Joining thread"), n as usize);
+}
+
+quickcheck! {
+    fn test_apply_batch(intervals : Vec<Interval<u8>>, changes : Vec<(bool, Interval<u8>)>) -> TestResult {
+        let tree = intervals.into_iter().collect::<IntervalTree<u8>>();
+        let mut expected = tree.clone();
+        for (add, interval) in &changes {
+            expected = if *add {
+                expected.insert(interval.clone())
+            } else {
+                expected.remove(interval)
+            };
+        }
+        let batched = tree.apply_batch(changes.into_iter().map(|(add, interval)| {
+            if add {
+                Change::Added(interval)
+            } else {
+                Change::Removed(interval)
+            }
+        }));
+
+        let mut expected = expected.iter().collect::<Vec<_>>();
+        expected.sort();
+        let mut actual = batched.iter().collect::<Vec<_>>();
+        actual.sort();
+        TestResult::from_bool(
+            batched.check_invariants().is_ok()
+                && actual == expected
+                && tree.apply_batch(Vec::new()).ptr_eq(&tree)
+                && tree.apply_batch(tree.diff(&batched)).diff(&batched).next().is_none()
+        )
+    }
+}
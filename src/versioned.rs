@@ -0,0 +1,297 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::SystemTime;
+
+use crate::shared::SharedPointerKind;
+use crate::{DefaultSharedPointerKind, IntervalTree};
+
+/// One recorded version of a VersionedIntervalTree
+pub struct Version<T: Ord + Clone, P: SharedPointerKind = DefaultSharedPointerKind> {
+    tree: IntervalTree<T, P>,
+    parent: Option<usize>,
+    label: String,
+    timestamp: SystemTime,
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> Version<T, P> {
+    /// Return the tree as of this version
+    pub fn tree(&self) -> &IntervalTree<T, P> {
+        &self.tree
+    }
+
+    /// Return the version this one was committed on top of, if it is still retained
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    /// Return the label given when this version was committed
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Return when this version was committed
+    pub fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> Clone for Version<T, P> {
+    fn clone(&self) -> Self {
+        Version {
+            tree: self.tree.clone(),
+            parent: self.parent,
+            label: self.label.clone(),
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+/// A history of IntervalTree versions with undo, redo and branching
+///
+/// Every committed version is numbered, labelled and timestamped. Since trees
+/// are persistent, all retained versions share whatever nodes they have in
+/// common, so keeping a long history is cheap.
+///
+/// Committing on top of a version that is not the latest starts a new branch;
+/// the old branch stays reachable with `checkout` until it is pruned.
+///
+/// # Example
+/// ```
+/// # use std::ops::Bound::*;
+/// # use im_interval_tree::{VersionedIntervalTree, Interval};
+/// let mut history : VersionedIntervalTree<u8> = VersionedIntervalTree::new();
+/// let empty = history.current_version();
+///
+/// history.update("add morning", |tree| {
+///     tree.insert(Interval::new(Included(9), Excluded(12)))
+/// });
+/// history.update("add afternoon", |tree| {
+///     tree.insert(Interval::new(Included(13), Excluded(17)))
+/// });
+/// assert_eq!(history.current().iter().count(), 2);
+///
+/// history.undo();
+/// assert_eq!(history.current().iter().count(), 1);
+/// history.redo();
+/// assert_eq!(history.current().iter().count(), 2);
+///
+/// assert_eq!(history.at_version(empty).unwrap().iter().count(), 0);
+/// ```
+pub struct VersionedIntervalTree<T: Ord + Clone, P: SharedPointerKind = DefaultSharedPointerKind> {
+    versions: BTreeMap<usize, Version<T, P>>,
+    current: usize,
+    next: usize,
+    /// Versions to return to with `redo`, most recently undone last
+    redo: Vec<usize>,
+}
+
+impl<T: Ord + Clone> VersionedIntervalTree<T> {
+    /// Construct a history whose only version is an empty IntervalTree
+    pub fn new() -> VersionedIntervalTree<T> {
+        Self::from_tree(IntervalTree::new())
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> VersionedIntervalTree<T, P> {
+    /// Construct a history whose only version is the given tree
+    pub fn from_tree(tree: IntervalTree<T, P>) -> VersionedIntervalTree<T, P> {
+        let mut versions = BTreeMap::new();
+        versions.insert(
+            0,
+            Version {
+                tree,
+                parent: None,
+                label: String::new(),
+                timestamp: SystemTime::now(),
+            },
+        );
+        VersionedIntervalTree {
+            versions,
+            current: 0,
+            next: 1,
+            redo: Vec::new(),
+        }
+    }
+
+    /// Return the tree as of the current version
+    pub fn current(&self) -> &IntervalTree<T, P> {
+        &self.versions[&self.current].tree
+    }
+
+    /// Return the number of the current version
+    pub fn current_version(&self) -> usize {
+        self.current
+    }
+
+    /// Return the tree as of the given version, if it is retained
+    pub fn at_version(&self, version: usize) -> Option<&IntervalTree<T, P>> {
+        self.versions.get(&version).map(|v| &v.tree)
+    }
+
+    /// Return the details of the given version, if it is retained
+    pub fn version(&self, version: usize) -> Option<&Version<T, P>> {
+        self.versions.get(&version)
+    }
+
+    /// Return an Iterator over all retained versions, oldest first
+    pub fn versions(&self) -> impl Iterator<Item = (usize, &Version<T, P>)> + '_ {
+        self.versions
+            .iter()
+            .map(|(&number, version)| (number, version))
+    }
+
+    /// Record a new version on top of the current one and make it current,
+    /// returning its number
+    ///
+    /// This discards the redo history, but versions that could have been
+    /// redone stay retained.
+    pub fn commit<L: Into<String>>(&mut self, label: L, tree: IntervalTree<T, P>) -> usize {
+        let number = self.next;
+        self.next += 1;
+        self.versions.insert(
+            number,
+            Version {
+                tree,
+                parent: Some(self.current),
+                label: label.into(),
+                timestamp: SystemTime::now(),
+            },
+        );
+        self.current = number;
+        self.redo.clear();
+        number
+    }
+
+    /// Commit the result of applying a change to the current version,
+    /// returning the new version's number
+    pub fn update<L, F>(&mut self, label: L, f: F) -> usize
+    where
+        L: Into<String>,
+        F: FnOnce(&IntervalTree<T, P>) -> IntervalTree<T, P>,
+    {
+        let tree = f(self.current());
+        self.commit(label, tree)
+    }
+
+    /// Move back to the parent of the current version, returning its tree
+    ///
+    /// Returns `None` and stays put if the current version has no retained parent.
+    pub fn undo(&mut self) -> Option<&IntervalTree<T, P>> {
+        let parent = self.versions[&self.current].parent?;
+        self.redo.push(self.current);
+        self.current = parent;
+        Some(self.current())
+    }
+
+    /// Move forward to the version most recently left with `undo`, returning its tree
+    ///
+    /// Returns `None` and stays put if there is nothing to redo.
+    pub fn redo(&mut self) -> Option<&IntervalTree<T, P>> {
+        self.current = self.redo.pop()?;
+        Some(self.current())
+    }
+
+    /// Make the given version current, so that the next commit branches from it
+    ///
+    /// This discards the redo history. Returns `None` and stays put if the
+    /// version is not retained.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{VersionedIntervalTree, Interval};
+    /// let mut history : VersionedIntervalTree<u8> = VersionedIntervalTree::new();
+    /// let base = history.update("base", |tree| tree.insert(Interval::new(Included(1), Excluded(2))));
+    /// let a = history.update("a", |tree| tree.insert(Interval::new(Included(3), Excluded(4))));
+    ///
+    /// history.checkout(base);
+    /// let b = history.update("b", |tree| tree.insert(Interval::new(Included(5), Excluded(6))));
+    ///
+    /// assert_eq!(history.version(a).unwrap().parent(), Some(base));
+    /// assert_eq!(history.version(b).unwrap().parent(), Some(base));
+    /// assert_eq!(history.at_version(a).unwrap().iter().count(), 2);
+    /// assert_eq!(history.at_version(b).unwrap().iter().count(), 2);
+    /// ```
+    pub fn checkout(&mut self, version: usize) -> Option<&IntervalTree<T, P>> {
+        if !self.versions.contains_key(&version) {
+            return None;
+        }
+        self.current = version;
+        self.redo.clear();
+        Some(self.current())
+    }
+
+    /// Drop every version for which `keep` returns false, except the current one
+    ///
+    /// Nodes are freed once no retained version uses them. The parent of each
+    /// retained version becomes its nearest retained ancestor.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::ops::Bound::*;
+    /// # use im_interval_tree::{VersionedIntervalTree, Interval};
+    /// let mut history : VersionedIntervalTree<u8> = VersionedIntervalTree::new();
+    /// for i in 0..10 {
+    ///     history.update("add", |tree| tree.insert(Interval::new(Included(i), Excluded(i + 1))));
+    /// }
+    ///
+    /// history.prune(|number, _| number >= 8);
+    /// assert_eq!(history.versions().count(), 3);
+    /// history.undo();
+    /// history.undo();
+    /// assert!(history.undo().is_none());
+    /// assert_eq!(history.current().iter().count(), 8);
+    /// ```
+    pub fn prune<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(usize, &Version<T, P>) -> bool,
+    {
+        let current = self.current;
+        let kept = self
+            .versions
+            .iter()
+            .filter(|(&number, version)| number == current || keep(number, version))
+            .map(|(&number, _)| number)
+            .collect::<BTreeSet<_>>();
+        let parents = self
+            .versions
+            .iter()
+            .map(|(&number, version)| (number, version.parent))
+            .collect::<BTreeMap<_, _>>();
+
+        self.versions.retain(|number, _| kept.contains(number));
+        for version in self.versions.values_mut() {
+            while let Some(parent) = version.parent {
+                if kept.contains(&parent) {
+                    break;
+                }
+                version.parent = parents[&parent];
+            }
+        }
+        self.redo.retain(|number| kept.contains(number));
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> Clone for VersionedIntervalTree<T, P> {
+    fn clone(&self) -> Self {
+        VersionedIntervalTree {
+            versions: self.versions.clone(),
+            current: self.current,
+            next: self.next,
+            redo: self.redo.clone(),
+        }
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> Default for VersionedIntervalTree<T, P> {
+    fn default() -> Self {
+        Self::from_tree(IntervalTree::new_with_ptr_kind())
+    }
+}
+
+impl<T: Ord + Clone, P: SharedPointerKind> From<IntervalTree<T, P>>
+    for VersionedIntervalTree<T, P>
+{
+    fn from(tree: IntervalTree<T, P>) -> Self {
+        Self::from_tree(tree)
+    }
+}